@@ -0,0 +1,82 @@
+//! Compares reading components with [EntityView::get] against a cached [CompRef].
+//!
+//! Run with `cargo bench --bench comp_ref`.
+
+use std::{hint::black_box, time::Instant};
+
+use simple_flecs::prelude::*;
+
+struct Position {
+    x: f32,
+    y: f32,
+}
+
+impl Component for Position {}
+
+#[allow(dead_code)]
+struct Velocity {
+    x: f32,
+    y: f32,
+}
+
+impl Component for Velocity {}
+
+const COUNT: usize = 1_000;
+const READS: usize = 1_000;
+
+fn world() -> (World, Vec<Entity>) {
+    let mut world = World::new();
+    world.component::<Position>(c"Position");
+    world.component::<Velocity>(c"Velocity");
+    let entities = world.spawn_batch(COUNT, |i| {
+        (
+            Position {
+                x: i as f32,
+                y: 0.0,
+            },
+            Velocity { x: 1.0, y: 1.0 },
+        )
+    });
+    (world, entities)
+}
+
+fn get(world: &World, entities: &[Entity]) {
+    let views: Vec<_> = entities.iter().map(|entity| world.view(*entity)).collect();
+    for _ in 0..READS {
+        for view in &views {
+            let position = unsafe { view.get::<Position>() }.unwrap();
+            black_box(position.x + position.y);
+        }
+    }
+}
+
+fn comp_ref(world: &World, entities: &[Entity]) {
+    let refs: Vec<_> = entities
+        .iter()
+        .map(|entity| world.view(*entity).comp_ref::<Position>())
+        .collect();
+    for _ in 0..READS {
+        for comp_ref in &refs {
+            let position = unsafe { comp_ref.get() }.unwrap();
+            black_box(position.x + position.y);
+        }
+    }
+}
+
+/// Time of reading every entity many times.
+fn measure(name: &str, func: fn(&World, &[Entity])) {
+    let (world, entities) = world();
+    let start = Instant::now();
+    func(&world, &entities);
+    let total = start.elapsed().as_secs_f64();
+    println!(
+        "{name}: {:.3} ms for {} reads",
+        total * 1000.0,
+        COUNT * READS
+    );
+}
+
+fn main() {
+    measure("EntityView::get", get);
+    measure("CompRef::get", comp_ref);
+}
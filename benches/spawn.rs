@@ -0,0 +1,85 @@
+//! Compares spawning entities one by one against [World::spawn_batch].
+//!
+//! Run with `cargo bench --bench spawn`.
+
+use std::{hint::black_box, time::Instant};
+
+use simple_flecs::prelude::*;
+
+#[allow(dead_code)]
+struct Position {
+    x: f32,
+    y: f32,
+}
+
+impl Component for Position {}
+
+#[allow(dead_code)]
+struct Velocity {
+    x: f32,
+    y: f32,
+}
+
+impl Component for Velocity {}
+
+struct Particle;
+
+impl Component for Particle {}
+
+const COUNT: usize = 50_000;
+const RUNS: u32 = 10;
+
+fn world() -> World {
+    let mut world = World::new();
+    world.component::<Position>(c"Position");
+    world.component::<Velocity>(c"Velocity");
+    world.component::<Particle>(c"Particle");
+    world
+}
+
+fn per_entity(world: &World) {
+    for i in 0..COUNT {
+        let entity = world.entity();
+        entity.set_comp(Position {
+            x: i as f32,
+            y: 0.0,
+        });
+        entity.set_comp(Velocity { x: 1.0, y: 1.0 });
+        entity.add(id::<Particle>());
+        black_box(entity.id());
+    }
+}
+
+fn batch(world: &World) {
+    let entities = world.spawn_batch(COUNT, |i| {
+        (
+            Position {
+                x: i as f32,
+                y: 0.0,
+            },
+            Velocity { x: 1.0, y: 1.0 },
+            Particle,
+        )
+    });
+    black_box(entities);
+}
+
+/// Average time of a run on a fresh world.
+fn measure(name: &str, func: fn(&World)) {
+    let mut total = 0.0;
+    for _ in 0..RUNS {
+        let world = world();
+        let start = Instant::now();
+        func(&world);
+        total += start.elapsed().as_secs_f64();
+    }
+    println!(
+        "{name}: {:.3} ms for {COUNT} entities",
+        total * 1000.0 / RUNS as f64
+    );
+}
+
+fn main() {
+    measure("per entity", per_entity);
+    measure("spawn_batch", batch);
+}
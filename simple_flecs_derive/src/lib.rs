@@ -0,0 +1,325 @@
+//! Derive macros for simple_flecs.
+
+use std::ffi::CString;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Delimiter, Spacing, Span, TokenStream as TokenStream2, TokenTree};
+use quote::{ToTokens, quote};
+use syn::{Data, DeriveInput, Field, Index, LitCStr, LitStr, Path, parse_macro_input};
+
+/// Derives `simple_flecs::component::Component`.
+///
+/// Supported attributes:
+/// - `#[flecs(symbol = "game.Position")]` symbol the component is registered under, defaults to
+///   the name of the type.
+/// - `#[flecs(traits(Sparse, CanToggle))]` component traits added on registration, single
+///   identifiers are looked up in `simple_flecs::flecs`.
+/// - `#[flecs(clone)]` installs a copy hook based on `Clone`, the type must also implement
+///   `Default` to construct the components copied into.
+/// - `#[flecs(default)]` sets `Component::DEFAULT` to `Default`, so that the component can be
+///   added without data.
+/// - `#[flecs(tag)]` marks the component as a tag, it must be zero sized.
+#[proc_macro_derive(Component, attributes(flecs))]
+pub fn derive_component(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand_component(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Derives `simple_flecs::bundle::Bundle` for a struct whose fields are distinct components.
+///
+/// Up to 12 fields are supported, the same as for tuples.
+#[proc_macro_derive(Bundle)]
+pub fn derive_bundle(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand_bundle(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Parsed `#[flecs(...)]` attributes.
+#[derive(Default)]
+struct FlecsAttributes {
+    symbol: Option<LitStr>,
+    traits: Vec<Path>,
+    clone: bool,
+    default: bool,
+    tag: bool,
+}
+
+fn parse_attributes(input: &DeriveInput) -> syn::Result<FlecsAttributes> {
+    let mut attributes = FlecsAttributes::default();
+    for attr in &input.attrs {
+        if !attr.path().is_ident("flecs") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("symbol") {
+                attributes.symbol = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("traits") {
+                meta.parse_nested_meta(|trait_meta| {
+                    attributes.traits.push(trait_meta.path);
+                    Ok(())
+                })
+            } else if meta.path.is_ident("clone") {
+                attributes.clone = true;
+                Ok(())
+            } else if meta.path.is_ident("default") {
+                attributes.default = true;
+                Ok(())
+            } else if meta.path.is_ident("tag") {
+                attributes.tag = true;
+                Ok(())
+            } else {
+                Err(meta.error("unknown flecs attribute"))
+            }
+        })?;
+    }
+    Ok(attributes)
+}
+
+fn expand_component(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let attributes = parse_attributes(input)?;
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    //symbol
+    let (symbol, span) = match &attributes.symbol {
+        Some(symbol) => (symbol.value(), symbol.span()),
+        None => (name.to_string(), Span::call_site()),
+    };
+    let symbol = CString::new(symbol)
+        .map_err(|_| syn::Error::new(span, "symbol must not contain a nul byte"))?;
+    let symbol = LitCStr::new(&symbol, span);
+
+    //traits
+    let traits = attributes.traits.iter().map(|path| {
+        if let Some(ident) = path.get_ident() {
+            quote! { ::simple_flecs::flecs::#ident }
+        } else {
+            quote! { #path }
+        }
+    });
+
+    //hooks
+    let hooks = attributes.clone.then(|| {
+        quote! {
+            component.hooks::<Self>().clone_copy().build();
+        }
+    });
+    let default = attributes.default.then(|| {
+        quote! {
+            const DEFAULT: ::core::option::Option<fn() -> Self> =
+                ::core::option::Option::Some(<Self as ::core::default::Default>::default);
+        }
+    });
+
+    //layout
+    let layout_hash = layout_hash(input);
+
+    //tags
+    let tag = attributes.tag.then(|| {
+        quote! {
+            const IS_TAG: bool = {
+                assert!(
+                    ::core::mem::size_of::<Self>() == 0,
+                    "tag components must be zero sized"
+                );
+                true
+            };
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::simple_flecs::component::Component for #name #ty_generics
+        #where_clause
+        {
+            #tag
+            const SYMBOL: ::core::option::Option<&'static ::core::ffi::CStr> =
+                ::core::option::Option::Some(#symbol);
+            const LAYOUT_HASH: u64 = #layout_hash;
+            #default
+
+            fn on_register(component: ::simple_flecs::component::ComponentView<'_>) {
+                #( component.add_trait(#traits); )*
+                #hooks
+            }
+        }
+    })
+}
+
+fn expand_bundle(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(name, "bundles must be structs"));
+    };
+    if data.fields.is_empty() || data.fields.len() > 12 {
+        return Err(syn::Error::new_spanned(
+            name,
+            "bundles must have between 1 and 12 fields",
+        ));
+    }
+
+    //fields of the struct and columns of the tuple
+    let types: Vec<_> = data.fields.iter().map(|field| &field.ty).collect();
+    let members: Vec<_> = data
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| match &field.ident {
+            Some(ident) => quote! { #ident },
+            None => {
+                let index = Index::from(index);
+                quote! { #index }
+            }
+        })
+        .collect();
+    let columns: Vec<_> = (0..types.len()).map(Index::from).collect();
+
+    Ok(quote! {
+        impl #impl_generics ::simple_flecs::bundle::Bundle for #name #ty_generics
+        #where_clause
+        {
+            type Columns = ( #( ::std::vec::Vec<#types>, )* );
+
+            fn ids(
+                world: &::simple_flecs::world::World,
+                ids: &mut ::std::vec::Vec<::simple_flecs::entity::Entity>,
+            ) {
+                #(
+                    ids.push(::simple_flecs::component::id::IdFetcher::retrieve_id(
+                        &::simple_flecs::component::id::id::<#types>(),
+                        world,
+                    ));
+                )*
+            }
+
+            fn push(self, columns: &mut Self::Columns) {
+                #( columns.#columns.push(self.#members); )*
+            }
+
+            fn column_ptrs(
+                columns: &mut Self::Columns,
+                ptrs: &mut ::std::vec::Vec<*mut ::core::ffi::c_void>,
+            ) {
+                #( ptrs.push(::simple_flecs::bundle::column_ptr(&mut columns.#columns)); )*
+            }
+
+            unsafe fn release(
+                columns: Self::Columns,
+                world: &::simple_flecs::world::World,
+                ids: &[::simple_flecs::entity::Entity],
+                moved: bool,
+            ) {
+                #(
+                    unsafe {
+                        ::simple_flecs::bundle::release_column(
+                            world,
+                            ids[#columns],
+                            columns.#columns,
+                            moved,
+                        )
+                    };
+                )*
+            }
+        }
+    })
+}
+
+/// Hashes names, types, offsets, sizes and alignments of the fields, so that binaries can check
+/// they agree on the layout.
+fn layout_hash(input: &DeriveInput) -> TokenStream2 {
+    let fields = match &input.data {
+        Data::Struct(data) => describe_fields(data.fields.iter(), true),
+        Data::Enum(data) => {
+            let variants = data.variants.iter().map(|variant| {
+                let name = variant.ident.to_string();
+                //offsets of variant fields cannot be taken on stable
+                let fields = describe_fields(variant.fields.iter(), false);
+                quote! { .name(#name) #fields }
+            });
+            quote! { #(#variants)* }
+        }
+        Data::Union(data) => describe_fields(data.fields.named.iter(), true),
+    };
+    quote! {
+        ::simple_flecs::component::layout::LayoutHasher::new() #fields .finish()
+    }
+}
+
+/// Describes names, types, offsets, sizes and alignments of fields to the layout hasher.
+fn describe_fields<'a>(fields: impl Iterator<Item = &'a Field>, offsets: bool) -> TokenStream2 {
+    let fields = fields.enumerate().map(|(index, field)| {
+        let ty = &field.ty;
+        let member = match &field.ident {
+            Some(ident) => quote! { #ident },
+            None => {
+                let index = Index::from(index);
+                quote! { #index }
+            }
+        };
+        let name = member.to_string();
+        let type_name = type_name(ty.to_token_stream());
+        let offset = offsets.then(|| quote! { .value(::core::mem::offset_of!(Self, #member)) });
+        quote! {
+            .name(#name)
+            .name(#type_name)
+            #offset
+            .value(::core::mem::size_of::<#ty>())
+            .value(::core::mem::align_of::<#ty>())
+        }
+    });
+    quote! { #(#fields)* }
+}
+
+/// Spells a type without the paths leading to its names, e.g. `Vec<u32>` for
+/// `std::vec::Vec<core::primitive::u32>`.
+///
+/// `type_name` cannot be called in constants yet, so types are identified by their spelling.
+fn type_name(tokens: TokenStream2) -> String {
+    let mut name = String::new();
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        match token {
+            TokenTree::Group(group) => {
+                let (open, close) = match group.delimiter() {
+                    Delimiter::Parenthesis => ("(", ")"),
+                    Delimiter::Bracket => ("[", "]"),
+                    Delimiter::Brace => ("{", "}"),
+                    Delimiter::None => ("", ""),
+                };
+                name.push_str(open);
+                name.push_str(&type_name(group.stream()));
+                name.push_str(close);
+            }
+            TokenTree::Ident(ident) => {
+                let is_path = matches!(
+                    tokens.peek(),
+                    Some(TokenTree::Punct(punct))
+                        if punct.as_char() == ':' && punct.spacing() == Spacing::Joint
+                );
+                if is_path {
+                    //skip the segment and its `::`
+                    tokens.next();
+                    tokens.next();
+                } else {
+                    name.push_str(&ident.to_string());
+                    name.push(' ');
+                }
+            }
+            TokenTree::Punct(punct)
+                if punct.as_char() == ':' && punct.spacing() == Spacing::Joint =>
+            {
+                //leading `::`
+                tokens.next();
+            }
+            token => name.push_str(&token.to_string()),
+        }
+    }
+    name
+}
@@ -0,0 +1,288 @@
+//! Bundles of components written together.
+//!
+//! A [Bundle] is a set of components, such as a tuple `(Position, Velocity)`. Writing a bundle
+//! at once moves an entity to its final table once, instead of once per component.
+//!
+//! Bundles can be derived for structs whose fields are components, with the `derive` feature.
+
+use std::{
+    ffi::c_void,
+    ptr::{null, null_mut},
+};
+
+use flecs_ecs_sys::*;
+
+use crate::{
+    component::{
+        Component,
+        id::{IdFetcher, id},
+    },
+    entity::{Entity, EntityView},
+    world::World,
+};
+
+/// Derives [Bundle] for a struct whose fields are distinct components.
+#[cfg(feature = "derive")]
+pub use simple_flecs_derive::Bundle;
+
+/// Set of distinct components written together.
+///
+/// Implemented for tuples of up to 12 components, tags included, and derivable for structs.
+pub trait Bundle: Sized + 'static {
+    /// Storage of many bundles, one column per component.
+    type Columns: Default;
+
+    /// Appends ids of the components, in order.
+    fn ids(world: &World, ids: &mut Vec<Entity>);
+
+    /// Appends the bundle to the columns.
+    fn push(self, columns: &mut Self::Columns);
+
+    /// Appends pointers to the data of the columns, in order, null for tags.
+    fn column_ptrs(columns: &mut Self::Columns, ptrs: &mut Vec<*mut c_void>);
+
+    /// Releases the columns after flecs has taken over their data, see [release_column].
+    ///
+    /// # Safety
+    ///
+    /// The data must have been copied into `world` by flecs, `ids` are the ids from [Self::ids].
+    unsafe fn release(columns: Self::Columns, world: &World, ids: &[Entity], moved: bool);
+}
+
+/// Retrieves ids of the components of a bundle.
+///
+/// # Panics
+///
+/// If a component is in the bundle twice, flecs would take over its data only once.
+fn bundle_ids<B: Bundle>(world: &World) -> Vec<Entity> {
+    let mut ids = Vec::new();
+    B::ids(world, &mut ids);
+    for (i, id) in ids.iter().enumerate() {
+        assert!(
+            !ids[..i].contains(id),
+            "bundle {:?} contains a component twice",
+            core::any::type_name::<B>()
+        );
+    }
+    ids
+}
+
+/// Pointer to the data of a column, null for tags.
+#[doc(hidden)]
+pub fn column_ptr<T: Component>(column: &mut Vec<T>) -> *mut c_void {
+    if T::IS_TAG {
+        null_mut()
+    } else {
+        column.as_mut_ptr() as *mut c_void
+    }
+}
+
+/// Releases a column after flecs has taken over its data.
+///
+/// When setting, flecs copies the data with the copy hook of the component if there is one, the
+/// column still owns its data then. Otherwise it copies the bytes, so the data now belongs to the
+/// world. When `moved`, as by bulk creation, flecs moves the data with the move hook or by copying
+/// the bytes, so the data always belongs to the world.
+///
+/// # Safety
+///
+/// The data must have been copied or moved by flecs into the component `id`.
+#[doc(hidden)]
+pub unsafe fn release_column<T: Component>(
+    world: &World,
+    id: Entity,
+    mut column: Vec<T>,
+    moved: bool,
+) {
+    let hooks = unsafe { ecs_get_hooks_id(world.ptr(), id).as_ref() };
+    if moved || !hooks.is_some_and(|hooks| hooks.copy.is_some()) {
+        unsafe { column.set_len(0) };
+    }
+}
+
+macro_rules! impl_bundle {
+    ($($name:ident $index:tt),+) => {
+        impl<$($name: Component),+> Bundle for ($($name,)+) {
+            type Columns = ($(Vec<$name>,)+);
+
+            fn ids(world: &World, ids: &mut Vec<Entity>) {
+                $(ids.push(id::<$name>().retrieve_id(world));)+
+            }
+
+            fn push(self, columns: &mut Self::Columns) {
+                $(columns.$index.push(self.$index);)+
+            }
+
+            fn column_ptrs(columns: &mut Self::Columns, ptrs: &mut Vec<*mut c_void>) {
+                $(ptrs.push(column_ptr(&mut columns.$index));)+
+            }
+
+            unsafe fn release(columns: Self::Columns, world: &World, ids: &[Entity], moved: bool) {
+                $(unsafe { release_column(world, ids[$index], columns.$index, moved) };)+
+            }
+        }
+    };
+}
+
+impl_bundle!(A 0);
+impl_bundle!(A 0, B 1);
+impl_bundle!(A 0, B 1, C 2);
+impl_bundle!(A 0, B 1, C 2, D 3);
+impl_bundle!(A 0, B 1, C 2, D 3, E 4);
+impl_bundle!(A 0, B 1, C 2, D 3, E 4, F 5);
+impl_bundle!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_bundle!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+impl_bundle!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8);
+impl_bundle!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9);
+impl_bundle!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10);
+impl_bundle!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11);
+
+impl World {
+    /// Creates many entities with the same components at once.
+    ///
+    /// The entities are created directly in their final table and the components are written a
+    /// column at a time, which is much faster than creating entities one by one. `func` creates
+    /// the bundle of the i-th entity.
+    ///
+    /// Returns the new entities.
+    ///
+    /// # Panics
+    ///
+    /// If a component is in the bundle twice.
+    pub fn spawn_batch<B: Bundle>(
+        &self,
+        count: usize,
+        mut func: impl FnMut(usize) -> B,
+    ) -> Vec<Entity> {
+        if count == 0 {
+            return Vec::new();
+        }
+        //ids of the final table
+        let mut desc = ecs_bulk_desc_t {
+            count: count as i32,
+            ..Default::default()
+        };
+        let ids = bundle_ids::<B>(self);
+        assert!(
+            ids.len() <= desc.ids.len(),
+            "bundle has too many components"
+        );
+        desc.ids[..ids.len()].copy_from_slice(&ids);
+        //gather the columns
+        let mut columns = B::Columns::default();
+        for i in 0..count {
+            func(i).push(&mut columns);
+        }
+        let mut ptrs = Vec::with_capacity(ids.len());
+        B::column_ptrs(&mut columns, &mut ptrs);
+        desc.data = ptrs.as_mut_ptr();
+        //create the entities
+        let entities = unsafe { ecs_bulk_init(self.ptr(), &desc as *const _) };
+        let entities = unsafe { std::slice::from_raw_parts(entities, count) }.to_vec();
+        //bulk creation moves the data into the world
+        unsafe { B::release(columns, self, &ids, true) };
+        self.resume_panic();
+        entities
+    }
+}
+
+impl World {
+    /// Creates a new entity with a bundle of components.
+    pub fn entity_with<B: Bundle>(&self, bundle: B) -> EntityView<'_> {
+        let entity = self.entity();
+        entity.insert(bundle);
+        entity
+    }
+}
+
+impl<'a> EntityView<'a> {
+    /// Sets a bundle of components to the entity.
+    ///
+    /// The entity moves to its final table once, then the data is written. While the world is
+    /// deferred, the components are set one by one.
+    ///
+    /// # Panics
+    ///
+    /// If a component is in the bundle twice.
+    pub fn insert<B: Bundle>(&self, bundle: B) {
+        let world = self.world;
+        let ids = bundle_ids::<B>(world);
+        //move to the final table at once
+        if !unsafe { ecs_is_deferred(world.ptr()) } {
+            let source = unsafe { ecs_get_table(world.ptr(), self.entity_id) };
+            let mut table = source;
+            let mut added = Vec::with_capacity(ids.len());
+            for id in &ids {
+                if source.is_null() || !unsafe { ecs_table_has_id(world.ptr(), source, *id) } {
+                    table = unsafe { ecs_table_add_id(world.ptr(), table, *id) };
+                    added.push(*id);
+                }
+            }
+            if !added.is_empty() {
+                self.commit(table, &mut added, &mut []);
+            }
+        }
+        //write the data
+        let mut columns = B::Columns::default();
+        bundle.push(&mut columns);
+        let mut ptrs = Vec::with_capacity(ids.len());
+        B::column_ptrs(&mut columns, &mut ptrs);
+        for (id, ptr) in ids.iter().zip(&ptrs) {
+            if ptr.is_null() {
+                unsafe { ecs_add_id(world.ptr(), self.entity_id, *id) };
+                continue;
+            }
+            let size = unsafe { (*ecs_get_type_info(world.ptr(), *id)).size } as usize;
+            unsafe { ecs_set_id(world.ptr(), self.entity_id, *id, size, *ptr) };
+        }
+        unsafe { B::release(columns, world, &ids, false) };
+        world.resume_panic();
+    }
+
+    /// Removes a bundle of components from the entity.
+    ///
+    /// The entity moves to its final table once. While the world is deferred, the components are
+    /// removed one by one.
+    pub fn remove_bundle<B: Bundle>(&self) {
+        let world = self.world;
+        let ids = bundle_ids::<B>(world);
+        if unsafe { ecs_is_deferred(world.ptr()) } {
+            for id in ids {
+                unsafe { ecs_remove_id(world.ptr(), self.entity_id, id) };
+            }
+        } else {
+            let mut table = unsafe { ecs_get_table(world.ptr(), self.entity_id) };
+            let mut removed = Vec::with_capacity(ids.len());
+            for id in ids {
+                if !table.is_null() && unsafe { ecs_table_has_id(world.ptr(), table, id) } {
+                    table = unsafe { ecs_table_remove_id(world.ptr(), table, id) };
+                    removed.push(id);
+                }
+            }
+            if !removed.is_empty() {
+                self.commit(table, &mut [], &mut removed);
+            }
+        }
+        world.resume_panic();
+    }
+
+    /// Moves the entity to a table, notifying about added and removed ids.
+    fn commit(&self, table: *mut ecs_table_t, added: &mut [Entity], removed: &mut [Entity]) {
+        let as_type = |ids: &mut [Entity]| ecs_type_t {
+            array: ids.as_mut_ptr(),
+            count: ids.len() as i32,
+        };
+        let (added, removed) = (as_type(added), as_type(removed));
+        let record = unsafe { ecs_record_find(self.world.ptr(), self.entity_id) };
+        unsafe {
+            ecs_commit(
+                self.world.ptr(),
+                self.entity_id,
+                record,
+                table,
+                if added.count == 0 { null() } else { &added },
+                if removed.count == 0 { null() } else { &removed },
+            )
+        };
+    }
+}
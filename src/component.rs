@@ -1,14 +1,24 @@
+pub mod hooks;
 pub mod id;
+pub mod id_view;
+pub mod layout;
 pub mod traits;
 
 use std::{any::Any, ffi::CStr};
 
 use crate::{
     entity::{Entity, EntityView},
+    flecs::{OnInstantiate, Override},
     world::World,
 };
 use flecs_ecs_sys::*;
+use hooks::HookContext;
 use id::IdFetcher;
+use id_view::IdView;
+
+/// Derives [Component], see the `simple_flecs_derive` crate for the supported attributes.
+#[cfg(feature = "derive")]
+pub use simple_flecs_derive::Component;
 
 /// Component for the ECS, works more as a marker.
 pub trait Component: Any + Sized {
@@ -22,6 +32,24 @@ pub trait Component: Any + Sized {
     ///
     /// Used only for flecs built-in components. You should not use it.
     const ID: Option<Entity> = None;
+    /// Symbol the component is registered under by [World::register].
+    ///
+    /// Set by the derive macro.
+    const SYMBOL: Option<&'static CStr> = None;
+    /// Hash of the fields of the component, part of its [layout::ComponentLayout].
+    ///
+    /// Set by the derive macro, 0 means unknown.
+    const LAYOUT_HASH: u64 = 0;
+    /// Constructs components added without data, installed as their ctor hook on registration.
+    ///
+    /// Data components can only be added by [id::id] if it is set, e.g. to `Some(Self::default)`
+    /// or by `#[flecs(default)]`, see [crate::entity::EntityView::add].
+    const DEFAULT: Option<fn() -> Self> = None;
+
+    /// Called after the component is registered by [World::register].
+    ///
+    /// Adds traits and hooks of the component, set by the derive macro.
+    fn on_register(_component: ComponentView<'_>) {}
 }
 
 /// Builder pattern for component manipulation.
@@ -86,4 +114,30 @@ impl<'a> ComponentView<'a> {
         let id = trait_id.retrieve_id(self.world);
         unsafe { ecs_add_id(self.world.ptr(), self.entity_id, id) }
     }
+
+    /// Sets what happens with the component when a prefab is instantiated.
+    ///
+    /// One of [Override](crate::flecs::Override), which copies it to the instance,
+    /// [Inherit](crate::flecs::Inherit), which shares it with the prefab, or
+    /// [DontInherit](crate::flecs::DontInherit).
+    ///
+    /// # Panics
+    ///
+    /// If the component is copied to instances, but it is dropped and has no copy hook, see
+    /// [World::component].
+    pub fn on_instantiate(&self, kind: impl IdFetcher) {
+        let kind = kind.retrieve_id(self.world);
+        assert!(
+            kind != id::id::<Override>().retrieve_id(self.world)
+                || HookContext::is_copyable(self.world, self.entity_id),
+            "{} cannot be copied to instances, it is dropped but not cloned",
+            IdView::new(self.world, self.entity_id)
+        );
+        //chosen explicitly
+        if let Some(ctx) = unsafe { HookContext::of(self.world, self.entity_id).as_mut() } {
+            ctx.inherit_default = false;
+        }
+        let id = IdView::pair(self.world, OnInstantiate, kind).id();
+        unsafe { ecs_add_id(self.world.ptr(), self.entity_id, id) }
+    }
 }
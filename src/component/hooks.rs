@@ -0,0 +1,446 @@
+use std::{ffi::c_void, marker::PhantomData, ptr::NonNull};
+
+use flecs_ecs_sys::*;
+
+use crate::{
+    entity::{Entity, EntityView},
+    flecs::Override,
+    world::{ComponentMap, World, abort_on_panic, catch_panic},
+};
+
+use super::{
+    Component, ComponentView,
+    id::{IdFetcher, id},
+};
+
+/// Type erased closure of an on_add, on_set or on_remove hook.
+pub(crate) type HookFn = Box<dyn Fn(EntityView<'_>, *mut c_void)>;
+
+/// Binding context of component hooks.
+#[repr(C)]
+pub(crate) struct HookContext {
+    /// This field must always be first!
+    pub(crate) component_map: *mut ComponentMap,
+    /// Always [Self::MAGIC], tells hook contexts apart from binding contexts set by other code.
+    magic: u64,
+    /// World the component is registered in.
+    pub(crate) world: *mut ecs_world_t,
+    pub(crate) on_add: Option<HookFn>,
+    pub(crate) on_set: Option<HookFn>,
+    pub(crate) on_remove: Option<HookFn>,
+    /// Whether the ctor hook constructs components with [Default].
+    pub(crate) default_ctor: bool,
+    /// Whether instances of prefabs inherit the component only because it cannot be copied, see
+    /// [World::component].
+    pub(crate) inherit_default: bool,
+}
+
+impl HookContext {
+    /// Identifies hook contexts, the low bits are bumped on every change of their fields.
+    pub(crate) const MAGIC: u64 = 0x5346_4c45_4353_0001;
+
+    /// Leaks a new hook context, it is freed by [hook_ctx_free].
+    pub(crate) fn leak(world: *mut ecs_world_t, component_map: *mut ComponentMap) -> *mut c_void {
+        Box::leak(Box::new(HookContext {
+            component_map,
+            magic: Self::MAGIC,
+            world,
+            on_add: None,
+            on_set: None,
+            on_remove: None,
+            default_ctor: false,
+            inherit_default: false,
+        })) as *mut _ as *mut c_void
+    }
+
+    /// Retrieves the world from a type info of a hook.
+    ///
+    /// # Safety
+    ///
+    /// The binding context of the type info must be a hook context.
+    pub(crate) unsafe fn world(type_info: *const ecs_type_info_t) -> *mut ecs_world_t {
+        let ctx = unsafe { (*type_info).hooks.binding_ctx } as *const HookContext;
+        unsafe { (*ctx).world }
+    }
+
+    /// Retrieves the hook context of a component, null if it has none.
+    pub(crate) fn of(world: &World, id: Entity) -> *mut HookContext {
+        match unsafe { ecs_get_type_info(world.ptr(), id).as_ref() } {
+            Some(type_info) => Self::from_hooks(&type_info.hooks),
+            None => std::ptr::null_mut(),
+        }
+    }
+
+    /// Retrieves the hook context of hooks, null if they have none or their binding context is
+    /// set by other code, such as another language binding.
+    ///
+    /// Binding contexts of other code are assumed to be at least as large as the first two
+    /// fields, only those are read to identify them.
+    pub(crate) fn from_hooks(hooks: &ecs_type_hooks_t) -> *mut HookContext {
+        let ctx = hooks.binding_ctx as *mut HookContext;
+        if ctx.is_null() {
+            return ctx;
+        }
+        if unsafe { (*ctx).magic } == Self::MAGIC {
+            ctx
+        } else {
+            std::ptr::null_mut()
+        }
+    }
+
+    /// Checks whether an id can be copied to instances of prefabs, i.e. it is a tag, it is not
+    /// dropped or it is copied by a copy hook.
+    ///
+    /// flecs copies the bytes of components without a copy hook, which then are dropped twice.
+    pub(crate) fn is_copyable(world: &World, id: Entity) -> bool {
+        match unsafe { ecs_get_type_info(world.ptr(), id).as_ref() } {
+            Some(type_info) => type_info.hooks.dtor.is_none() || type_info.hooks.copy.is_some(),
+            None => true,
+        }
+    }
+
+    /// Checks whether an id can be added without data, i.e. it is a tag or its data is
+    /// constructed by [Component::DEFAULT] or [ComponentHooks::ctor_default].
+    ///
+    /// Components registered by flecs itself or other code are constructed as they define, or
+    /// zeroed as in C.
+    pub(crate) fn is_constructible(world: &World, id: Entity) -> bool {
+        let Some(type_info) = (unsafe { ecs_get_type_info(world.ptr(), id).as_ref() }) else {
+            return true;
+        };
+        match unsafe { Self::from_hooks(&type_info.hooks).as_ref() } {
+            Some(ctx) => ctx.default_ctor,
+            None => true,
+        }
+    }
+}
+
+pub(crate) unsafe extern "C" fn hook_ctx_free(ctx: *mut c_void) {
+    // SAFETY:
+    // ctx is a pointer to HookContext, so we can safely cast it back.
+    let _ = unsafe { Box::from_raw(ctx as *mut HookContext) };
+}
+
+/// Builder for lifecycle hooks of a component.
+///
+/// Created by [ComponentView::hooks], installed by [Self::build]. Hooks not set by the builder
+/// are kept as they are. Hooks must be set before the component is used.
+pub struct ComponentHooks<'a, T: Component> {
+    view: ComponentView<'a>,
+    hooks: ecs_type_hooks_t,
+    on_add: Option<HookFn>,
+    on_set: Option<HookFn>,
+    on_remove: Option<HookFn>,
+    default_ctor: bool,
+    __m: PhantomData<fn(T)>,
+}
+
+impl<'a> ComponentView<'a> {
+    /// Starts building lifecycle hooks of a component.
+    ///
+    /// # Panics
+    ///
+    /// If `T` is not the data component this view refers to.
+    pub fn hooks<T: Component>(self) -> ComponentHooks<'a, T> {
+        const {
+            if T::IS_TAG {
+                panic!("tags cannot have lifecycle hooks");
+            }
+        }
+        assert!(
+            id::<T>().retrieve_id(self.world) == self.entity_id,
+            "hooks of {} must be installed on its own component",
+            core::any::type_name::<T>()
+        );
+        //start from the current hooks
+        let current = unsafe { ecs_get_hooks_id(self.world.ptr(), self.entity_id) };
+        let hooks = match unsafe { current.as_ref() } {
+            Some(hooks) => *hooks,
+            None => ecs_type_hooks_t::default(),
+        };
+        ComponentHooks {
+            view: self,
+            hooks,
+            on_add: None,
+            on_set: None,
+            on_remove: None,
+            default_ctor: false,
+            __m: PhantomData,
+        }
+    }
+}
+
+impl<'a, T: Component> ComponentHooks<'a, T> {
+    /// Constructs new components with [Default].
+    ///
+    /// Without it, flecs zeroes the memory of new components and they cannot be added to
+    /// entities without data. Only ids known at runtime can be added this way, adding by
+    /// [id] requires [Component::DEFAULT], see [EntityView::add].
+    pub fn ctor_default(mut self) -> Self
+    where
+        T: Default,
+    {
+        self.hooks.ctor = Some(ctor_callback::<T>);
+        self.default_ctor = true;
+        self
+    }
+
+    /// Moves components by copying their bytes, as Rust does.
+    ///
+    /// Move assignment swaps the components, so that the source stays valid until it is dropped.
+    pub fn moves(mut self) -> Self {
+        self.hooks.move_ = Some(move_callback::<T>);
+        self.hooks.move_ctor = Some(move_ctor_callback::<T>);
+        self.hooks.ctor_move_dtor = Some(move_ctor_callback::<T>);
+        self.hooks.move_dtor = Some(move_dtor_callback::<T>);
+        self
+    }
+
+    /// Copies components with [Clone].
+    ///
+    /// flecs copies into constructed components, so this also constructs them with [Default],
+    /// see [Self::ctor_default].
+    pub fn clone_copy(mut self) -> Self
+    where
+        T: Clone + Default,
+    {
+        self.hooks.copy = Some(copy_callback::<T>);
+        self.hooks.copy_ctor = Some(copy_ctor_callback::<T>);
+        self.ctor_default()
+    }
+
+    /// Sets a hook invoked whenever the component is added to an entity.
+    pub fn on_add<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(EntityView<'_>, &mut T) + 'static,
+    {
+        self.on_add = Some(erase_hook(hook));
+        self.hooks.on_add = Some(on_add_callback::<T>);
+        self
+    }
+
+    /// Sets a hook invoked whenever the component is set.
+    pub fn on_set<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(EntityView<'_>, &mut T) + 'static,
+    {
+        self.on_set = Some(erase_hook(hook));
+        self.hooks.on_set = Some(on_set_callback::<T>);
+        self
+    }
+
+    /// Sets a hook invoked whenever the component is removed from an entity.
+    pub fn on_remove<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(EntityView<'_>, &mut T) + 'static,
+    {
+        self.on_remove = Some(erase_hook(hook));
+        self.hooks.on_remove = Some(on_remove_callback::<T>);
+        self
+    }
+
+    /// Installs the hooks.
+    pub fn build(mut self) -> ComponentView<'a> {
+        let world = self.view.world;
+        //make sure there is a context for closures, replacing one set by other code
+        if HookContext::from_hooks(&self.hooks).is_null() {
+            self.hooks.binding_ctx = HookContext::leak(world.ptr(), world.component_map.as_ptr());
+            self.hooks.binding_ctx_free = Some(hook_ctx_free);
+        }
+        let ctx = unsafe { HookContext::from_hooks(&self.hooks).as_mut() }.unwrap();
+        if self.on_add.is_some() {
+            ctx.on_add = self.on_add.take();
+        }
+        if self.on_set.is_some() {
+            ctx.on_set = self.on_set.take();
+        }
+        if self.on_remove.is_some() {
+            ctx.on_remove = self.on_remove.take();
+        }
+        ctx.default_ctor |= self.default_ctor;
+        //set hooks
+        unsafe { ecs_set_hooks_id(world.ptr(), self.view.entity_id, &self.hooks as *const _) };
+        //it can be copied to instances now
+        if self.hooks.copy.is_some() && ctx.inherit_default {
+            self.view.on_instantiate(Override);
+        }
+        self.view
+    }
+}
+
+/// Completes the hooks of a component registered by an older version of a reloaded plugin.
+///
+/// flecs only replaces the hooks which are set, so every hook the component can have is set,
+/// otherwise the old one would keep pointing into the old library. Missing hooks behave as flecs
+/// does without them. Copy hooks are left to clonable components, as on the first registration.
+pub(crate) fn rebound_hooks<T: Component>(hooks: ecs_type_hooks_t) -> ecs_type_hooks_t {
+    ecs_type_hooks_t {
+        ctor: hooks.ctor.or(Some(zero_ctor_callback)),
+        dtor: hooks.dtor.or(Some(dtor_callback::<T>)),
+        move_: hooks.move_.or(Some(move_callback::<T>)),
+        move_ctor: hooks.move_ctor.or(Some(move_ctor_callback::<T>)),
+        ctor_move_dtor: hooks.ctor_move_dtor.or(Some(move_ctor_callback::<T>)),
+        move_dtor: hooks.move_dtor.or(Some(move_dtor_callback::<T>)),
+        on_add: hooks.on_add.or(Some(on_add_callback::<T>)),
+        on_set: hooks.on_set.or(Some(on_set_callback::<T>)),
+        on_remove: hooks.on_remove.or(Some(on_remove_callback::<T>)),
+        ..hooks
+    }
+}
+
+/// Erases the component type of a hook closure.
+fn erase_hook<T: Component>(hook: impl Fn(EntityView<'_>, &mut T) + 'static) -> HookFn {
+    Box::new(move |entity, ptr| hook(entity, unsafe { &mut *(ptr as *mut T) }))
+}
+
+unsafe extern "C" fn ctor_callback<T: Component + Default>(
+    ptr: *mut c_void,
+    count: i32,
+    _type_info: *const ecs_type_info_t,
+) {
+    let ptr = ptr as *mut T;
+    //a panic would leave the components uninitialized
+    abort_on_panic(|| {
+        for i in 0..count as usize {
+            unsafe { std::ptr::write(ptr.add(i), T::default()) };
+        }
+    });
+}
+
+pub(crate) unsafe extern "C" fn default_ctor_callback<T: Component>(
+    ptr: *mut c_void,
+    count: i32,
+    _type_info: *const ecs_type_info_t,
+) {
+    let ptr = ptr as *mut T;
+    //a panic would leave the components uninitialized
+    abort_on_panic(|| {
+        let default = T::DEFAULT.expect("installed only for components with a constructor");
+        for i in 0..count as usize {
+            unsafe { std::ptr::write(ptr.add(i), default()) };
+        }
+    });
+}
+
+unsafe extern "C" fn zero_ctor_callback(
+    ptr: *mut c_void,
+    count: i32,
+    type_info: *const ecs_type_info_t,
+) {
+    //components without a constructor are zeroed, as by flecs
+    let size = unsafe { (*type_info).size } as usize;
+    unsafe { std::ptr::write_bytes(ptr as *mut u8, 0, size * count as usize) };
+}
+
+pub(crate) unsafe extern "C" fn dtor_callback<T: Component>(
+    ptr: *mut c_void,
+    count: i32,
+    type_info: *const ecs_type_info_t,
+) {
+    let world = unsafe { HookContext::world(type_info) };
+    let ptr = ptr as *mut T;
+    unsafe {
+        catch_panic(world, (), || {
+            std::ptr::slice_from_raw_parts_mut(ptr, count as usize).drop_in_place();
+        })
+    };
+}
+
+unsafe extern "C" fn copy_callback<T: Component + Clone>(
+    dst: *mut c_void,
+    src: *const c_void,
+    count: i32,
+    _type_info: *const ecs_type_info_t,
+) {
+    let (dst, src) = (dst as *mut T, src as *const T);
+    //a panic would leave the components half copied
+    abort_on_panic(|| {
+        for i in 0..count as usize {
+            unsafe { (*dst.add(i)).clone_from(&*src.add(i)) };
+        }
+    });
+}
+
+pub(crate) unsafe extern "C" fn copy_ctor_callback<T: Component + Clone>(
+    dst: *mut c_void,
+    src: *const c_void,
+    count: i32,
+    _type_info: *const ecs_type_info_t,
+) {
+    let (dst, src) = (dst as *mut T, src as *const T);
+    //a panic would leave the components uninitialized
+    abort_on_panic(|| {
+        for i in 0..count as usize {
+            unsafe { std::ptr::write(dst.add(i), (*src.add(i)).clone()) };
+        }
+    });
+}
+
+unsafe extern "C" fn move_callback<T: Component>(
+    dst: *mut c_void,
+    src: *mut c_void,
+    count: i32,
+    _type_info: *const ecs_type_info_t,
+) {
+    //the source is still dropped later, so it takes over the old destination
+    unsafe { std::ptr::swap_nonoverlapping(dst as *mut T, src as *mut T, count as usize) };
+}
+
+unsafe extern "C" fn move_ctor_callback<T: Component>(
+    dst: *mut c_void,
+    src: *mut c_void,
+    count: i32,
+    _type_info: *const ecs_type_info_t,
+) {
+    unsafe { std::ptr::copy_nonoverlapping(src as *const T, dst as *mut T, count as usize) };
+}
+
+unsafe extern "C" fn move_dtor_callback<T: Component>(
+    dst: *mut c_void,
+    src: *mut c_void,
+    count: i32,
+    type_info: *const ecs_type_info_t,
+) {
+    //drop the old destination first
+    unsafe { dtor_callback::<T>(dst, count, type_info) };
+    unsafe { std::ptr::copy_nonoverlapping(src as *const T, dst as *mut T, count as usize) };
+}
+
+unsafe extern "C" fn on_add_callback<T: Component>(iter: *mut ecs_iter_t) {
+    unsafe { invoke_hook::<T>(iter, |ctx| &ctx.on_add) };
+}
+
+unsafe extern "C" fn on_set_callback<T: Component>(iter: *mut ecs_iter_t) {
+    unsafe { invoke_hook::<T>(iter, |ctx| &ctx.on_set) };
+}
+
+unsafe extern "C" fn on_remove_callback<T: Component>(iter: *mut ecs_iter_t) {
+    unsafe { invoke_hook::<T>(iter, |ctx| &ctx.on_remove) };
+}
+
+/// Calls a hook closure for every entity of the iterator.
+///
+/// # Safety
+///
+/// The callback context of the iterator must be a hook context of component `T`.
+unsafe fn invoke_hook<T: Component>(
+    iter: *mut ecs_iter_t,
+    select: fn(&HookContext) -> &Option<HookFn>,
+) {
+    let world = unsafe { (*iter).world };
+    let ctx = unsafe { ((*iter).callback_ctx as *const HookContext).as_ref() }.unwrap();
+    let Some(hook) = select(ctx) else {
+        return;
+    };
+    unsafe {
+        catch_panic(world, (), || {
+            let world_ref = World::from_ptr_and_map(world, ctx.component_map);
+            let data = ecs_field_w_size(iter, size_of::<T>(), 0) as *mut T;
+            let data = NonNull::new(data).expect("hook invoked without component data");
+            for row in 0..(*iter).count as usize {
+                let entity = world_ref.view(*(*iter).entities.add(row));
+                hook(entity, data.as_ptr().add(row) as *mut c_void);
+            }
+        })
+    };
+}
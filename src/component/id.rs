@@ -1,13 +1,11 @@
 use std::any::TypeId;
 
-use flecs_ecs_sys::ecs_make_pair;
-
 use crate::{
     entity::{Entity, EntityView},
     world::World,
 };
 
-use super::{Component, ComponentView, traits::ComponentOrPair};
+use super::{Component, ComponentView, id_view::IdView, traits::ComponentOrPair};
 
 /// Fetch component id from the world.
 #[derive(Clone, Copy)]
@@ -76,13 +74,21 @@ impl<T: Component> IdFetcher for Id<T> {
             return id;
         }
         //retrieve dynamicaly
-        let Some(id) = unsafe { world.component_map.as_ref() }.get(&TypeId::of::<T>()) else {
+        let cached = unsafe { world.component_map.as_ref() }
+            .borrow()
+            .get(&TypeId::of::<T>())
+            .copied();
+        if let Some(id) = cached {
+            return id;
+        }
+        //fallback to the stable symbol
+        let Some(id) = world.resolve_symbol::<T>() else {
             panic!(
                 "component {:?} not implemented",
                 core::any::type_name::<T>()
             )
         };
-        *id
+        id
     }
 }
 
@@ -98,6 +104,6 @@ where
     fn retrieve_id(&self, world: &World) -> Entity {
         let left = self.0.retrieve_id(world);
         let right = self.1.retrieve_id(world);
-        unsafe { ecs_make_pair(left, right) }
+        IdView::pair(world, left, right).id()
     }
 }
@@ -0,0 +1,149 @@
+use std::{ffi::CStr, fmt};
+
+use flecs_ecs_sys::*;
+
+use crate::{
+    c_types::{ECS_PAIR, RUST_ECS_COMPONENT_MASK, RUST_ecs_id_FLAGS_MASK},
+    entity::{Entity, EntityView},
+    world::World,
+};
+
+use super::id::{IdFetcher, UnknownType};
+
+/// View of an id, which is an entity, a component or a pair, possibly with id flags.
+#[derive(Debug, Clone, Copy)]
+pub struct IdView<'a> {
+    pub(crate) world: &'a World,
+    pub(crate) id: Entity,
+}
+
+impl<'a> PartialEq for IdView<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<'a> Eq for IdView<'a> {}
+
+impl<'a> IdView<'a> {
+    /// Creates a view of an id.
+    #[inline]
+    pub fn new(world: &'a World, id: Entity) -> Self {
+        Self { world, id }
+    }
+
+    /// Creates a view of a pair.
+    pub fn pair(world: &'a World, first: impl IdFetcher, second: impl IdFetcher) -> Self {
+        let first = first.retrieve_id(world);
+        let second = second.retrieve_id(world);
+        Self {
+            world,
+            id: unsafe { ecs_make_pair(first, second) },
+        }
+    }
+
+    /// Gets the raw id.
+    #[inline]
+    pub fn id(&self) -> Entity {
+        self.id
+    }
+
+    /// Is the id a pair?
+    #[inline]
+    pub fn is_pair(&self) -> bool {
+        self.id & RUST_ecs_id_FLAGS_MASK == ECS_PAIR
+    }
+
+    /// Gets the id flags, such as [crate::flecs::id_flags::Toggle].
+    #[inline]
+    pub fn flags(&self) -> Entity {
+        self.id & RUST_ecs_id_FLAGS_MASK
+    }
+
+    /// Checks whether the id has an id flag, such as [crate::flecs::id_flags::AutoOverride].
+    pub fn has_flag(&self, flag: impl IdFetcher) -> bool {
+        let flag = flag.retrieve_id(self.world) & RUST_ecs_id_FLAGS_MASK;
+        flag != 0 && self.id & flag == flag
+    }
+
+    /// Does the id contain a wildcard, such as `(Likes, *)`?
+    #[inline]
+    pub fn is_wildcard(&self) -> bool {
+        unsafe { ecs_id_is_wildcard(self.id) }
+    }
+
+    /// Gets the entity of an id which is not a pair, without flags.
+    pub fn entity(&self) -> Option<EntityView<'a>> {
+        if self.is_pair() {
+            return None;
+        }
+        self.alive(self.id & RUST_ECS_COMPONENT_MASK)
+    }
+
+    /// Gets the first element of a pair.
+    pub fn first(&self) -> Option<EntityView<'a>> {
+        if !self.is_pair() {
+            return None;
+        }
+        self.alive((self.id & RUST_ECS_COMPONENT_MASK) >> 32)
+    }
+
+    /// Gets the second element of a pair.
+    pub fn second(&self) -> Option<EntityView<'a>> {
+        if !self.is_pair() {
+            return None;
+        }
+        self.alive(self.id as u32 as Entity)
+    }
+
+    /// Is the id a tag, i.e. it holds no data?
+    pub fn is_tag(&self) -> bool {
+        unsafe { ecs_get_typeid(self.world.ptr(), self.id) == 0 }
+    }
+
+    /// Gets the component which holds the data of the id, none for tags.
+    ///
+    /// For pairs it is either the first or the second element.
+    pub fn type_id(&self) -> Option<EntityView<'a>> {
+        let type_id = unsafe { ecs_get_typeid(self.world.ptr(), self.id) };
+        if type_id == 0 {
+            None
+        } else {
+            Some(self.world.view(type_id))
+        }
+    }
+
+    /// Gets the type info of the data of the id, none for tags.
+    pub fn type_info(&self) -> Option<&'a ecs_type_info_t> {
+        unsafe { ecs_get_type_info(self.world.ptr(), self.id).as_ref() }
+    }
+
+    /// Restores the generation of an entity stripped of it in a pair.
+    fn alive(&self, entity: Entity) -> Option<EntityView<'a>> {
+        let entity = unsafe { ecs_get_alive(self.world.ptr(), entity) };
+        if entity == 0 {
+            None
+        } else {
+            Some(self.world.view(entity))
+        }
+    }
+}
+
+impl<'a> fmt::Display for IdView<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let id_str = unsafe { ecs_id_str(self.world.ptr(), self.id) };
+        let result = f.write_str(&unsafe { CStr::from_ptr(id_str) }.to_string_lossy());
+        //free pointer
+        let api = unsafe { ecs_os_get_api() };
+        unsafe { (api.free_.unwrap())(id_str as *mut std::ffi::c_void) };
+        result
+    }
+}
+
+//Id fetching capabilities for IdView
+impl<'a> IdFetcher for IdView<'a> {
+    type CompPair = UnknownType;
+    fn retrieve_id(&self, _world: &World) -> Entity {
+        self.id
+    }
+}
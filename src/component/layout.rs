@@ -0,0 +1,127 @@
+use std::{
+    ffi::{CStr, CString},
+    fmt,
+};
+
+use super::Component;
+
+/// Layout fingerprint of a data component.
+///
+/// Stored on the component entity, so that binaries registering the same symbol can check they
+/// agree on the memory layout.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComponentLayout {
+    /// Size of the component in bytes.
+    pub size: u32,
+    /// Alignment of the component in bytes.
+    pub alignment: u32,
+    /// Hash of the fields of the component, 0 if unknown.
+    pub hash: u64,
+}
+
+/// Symbol the layout component is registered under.
+pub(crate) const LAYOUT_SYMBOL: &CStr = c"simple_flecs.ComponentLayout";
+
+impl Component for ComponentLayout {
+    const SYMBOL: Option<&'static CStr> = Some(LAYOUT_SYMBOL);
+}
+
+impl ComponentLayout {
+    /// Fingerprint of a component type.
+    pub const fn of<T: Component>() -> Self {
+        Self {
+            size: size_of::<T>() as u32,
+            alignment: align_of::<T>() as u32,
+            hash: T::LAYOUT_HASH,
+        }
+    }
+
+    /// Do the layouts describe the same memory?
+    ///
+    /// Field hashes are only compared when both are known.
+    pub fn is_compatible(&self, other: &Self) -> bool {
+        self.size == other.size
+            && self.alignment == other.alignment
+            && (self.hash == 0 || other.hash == 0 || self.hash == other.hash)
+    }
+}
+
+/// FNV-1a hasher of the fields of a component, used by the derive macro.
+///
+/// Fields are described by their names, types, offsets, sizes and alignments, so that renaming a
+/// field or changing its type changes the hash. Types are identified by their names without
+/// paths, since their full names are not available in constants, so spelling out the path of a
+/// type does not change the hash, while using an alias does. Types of the same name from
+/// different modules are not told apart.
+#[derive(Debug, Clone, Copy)]
+pub struct LayoutHasher(u64);
+
+impl LayoutHasher {
+    /// Creates a hasher with no fields.
+    pub const fn new() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+
+    /// Hashes a name of a field or variant.
+    pub const fn name(self, name: &str) -> Self {
+        let bytes = name.as_bytes();
+        let mut hash = self.0;
+        let mut index = 0;
+        while index < bytes.len() {
+            hash ^= bytes[index] as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+            index += 1;
+        }
+        //separator, so that names cannot run into each other
+        hash ^= 0xff;
+        Self(hash.wrapping_mul(0x100000001b3))
+    }
+
+    /// Hashes an offset, size or alignment.
+    pub const fn value(self, value: usize) -> Self {
+        let bytes = (value as u64).to_le_bytes();
+        let mut hash = self.0;
+        let mut index = 0;
+        while index < bytes.len() {
+            hash ^= bytes[index] as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+            index += 1;
+        }
+        Self(hash)
+    }
+
+    /// Retrieves the hash, 0 is reserved for unknown layouts.
+    pub const fn finish(self) -> u64 {
+        if self.0 == 0 { 1 } else { self.0 }
+    }
+}
+
+impl Default for LayoutHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Error of registering a component whose layout differs from the already registered one.
+#[derive(Debug, Clone)]
+pub struct LayoutMismatch {
+    /// Symbol of the component.
+    pub symbol: CString,
+    /// Layout the component was registered with.
+    pub registered: ComponentLayout,
+    /// Layout of the type being registered.
+    pub requested: ComponentLayout,
+}
+
+impl fmt::Display for LayoutMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "component {:?} is registered with layout {:?}, but the type has layout {:?}",
+            self.symbol, self.registered, self.requested
+        )
+    }
+}
+
+impl std::error::Error for LayoutMismatch {}
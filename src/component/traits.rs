@@ -6,6 +6,9 @@ pub trait ComponentOrPair {
     const IS_PAIR: bool = false;
     /// Is a tag pair.
     const IS_TAG: bool = false;
+    /// Can it be added without data, i.e. is it a tag or is its data constructed by
+    /// [Component::DEFAULT]?
+    const IS_CONSTRUCTIBLE: bool = true;
     /// Type of first one.
     type First: Component;
     /// Type of Second one.
@@ -26,6 +29,7 @@ where
 {
     const IS_PAIR: bool = false;
     const IS_TAG: bool = T::IS_TAG;
+    const IS_CONSTRUCTIBLE: bool = T::IS_TAG || T::DEFAULT.is_some();
     type First = T;
     type Second = EmptyType;
 }
@@ -37,6 +41,12 @@ where
 {
     const IS_PAIR: bool = true;
     const IS_TAG: bool = L::IS_TAG && R::IS_TAG;
+    //the data of a pair is the first component, unless it is a tag
+    const IS_CONSTRUCTIBLE: bool = if L::IS_TAG {
+        R::IS_TAG || R::DEFAULT.is_some()
+    } else {
+        L::DEFAULT.is_some()
+    };
     type First = L;
     type Second = R;
 }
@@ -1,3 +1,9 @@
+pub mod comp_ref;
+pub mod guard;
+pub mod hierarchy;
+pub mod iter;
+pub mod relationship;
+
 use std::{
     ffi::{CStr, c_void},
     mem::ManuallyDrop,
@@ -7,13 +13,20 @@ use std::{
 use flecs_ecs_sys::*;
 
 use crate::{
+    c_types::{ECS_AUTO_OVERRIDE, ECS_IS_A},
     component::{
         Component,
+        hooks::HookContext,
         id::{IdFetcher, id},
+        id_view::IdView,
+        traits::ComponentOrPair,
     },
+    flecs::{IsA, SlotOf},
     world::World,
 };
 
+use guard::Borrow;
+
 /// Entity handle.
 pub type Entity = ecs_entity_t;
 
@@ -114,6 +127,7 @@ impl<'a> EntityView<'a> {
     /// Clear the entity.
     pub fn clear(self) -> EntityView<'a> {
         unsafe { ecs_clear(self.world.ptr(), self.entity_id) }
+        self.world.resume_panic();
         self
     }
 
@@ -122,6 +136,7 @@ impl<'a> EntityView<'a> {
         unsafe {
             ecs_delete(self.world.ptr(), self.entity_id);
         }
+        self.world.resume_panic();
     }
 }
 
@@ -136,16 +151,26 @@ impl<'a> From<EntityView<'a>> for Entity {
 //------------------------------------------------------------------------------
 
 impl<'a> EntityView<'a> {
-    /// Adds a tag from an id to the entity.
+    /// Adds an id to the entity.
+    ///
+    /// Tags are components without data. Data components must be constructed by their hooks,
+    /// since they would be uninitialized otherwise, which is not allowed in Rust. Components
+    /// added by [id] must have a [Component::DEFAULT] constructor, otherwise it does not compile.
     ///
-    /// Tags are components without data.
+    /// # Panics
     ///
-    /// # Note
-    /// Cannot add data components since they would be unitialized which is not allowed in Rust.
-    /// TODO: Add a check against that.
-    pub fn add(&self, id: impl IdFetcher) {
+    /// If the id is only known at runtime and it is a data component constructed neither by
+    /// [Component::DEFAULT] nor by [crate::component::hooks::ComponentHooks::ctor_default].
+    pub fn add<I: IdFetcher>(&self, id: I) {
+        const {
+            if !<I::CompPair as ComponentOrPair>::IS_CONSTRUCTIBLE {
+                panic!("cannot add a data component without data, it has no Component::DEFAULT");
+            }
+        }
         let id = id.retrieve_id(self.world);
+        self.assert_constructible(id);
         unsafe { ecs_add_id(self.world.ptr(), self.entity_id, id) }
+        self.world.resume_panic();
     }
 
     /// Sets a component to an entity.
@@ -168,15 +193,13 @@ impl<'a> EntityView<'a> {
                 &my_data as *const _ as *const c_void,
             );
         }
+        self.world.resume_panic();
     }
 
     /// Sets a component pair to an entity, where the first is a component and the second is a
     /// tag.
     pub fn set_first<F: Component>(&self, first: F, second: impl IdFetcher) {
-        //get ids
-        let first_id = id::<F>().retrieve_id(self.world);
-        let second_id = second.retrieve_id(self.world);
-        let pair_id = unsafe { ecs_make_pair(first_id, second_id) };
+        let pair_id = IdView::pair(self.world, id::<F>(), second).id();
         //prevent dropping, since it copies whatever is there, don't want to accidentaly deallocate
         let my_data = ManuallyDrop::new(first);
         unsafe {
@@ -188,15 +211,13 @@ impl<'a> EntityView<'a> {
                 &my_data as *const _ as *const c_void,
             );
         }
+        self.world.resume_panic();
     }
 
     /// Sets a component pair to an entity, where the second is a component and the first is a
     /// tag.
     pub fn set_second<F: Component>(&self, first: impl IdFetcher, second: F) {
-        //get ids
-        let first_id = first.retrieve_id(self.world);
-        let second_id = id::<F>().retrieve_id(self.world);
-        let pair_id = unsafe { ecs_make_pair(first_id, second_id) };
+        let pair_id = IdView::pair(self.world, first, id::<F>()).id();
         //prevent dropping, since it copies whatever is there, don't want to accidentaly deallocate
         let my_data = ManuallyDrop::new(second);
         unsafe {
@@ -208,6 +229,7 @@ impl<'a> EntityView<'a> {
                 &my_data as *const _ as *const c_void,
             );
         }
+        self.world.resume_panic();
     }
 
     /// Gets a component from the entity.
@@ -229,10 +251,7 @@ impl<'a> EntityView<'a> {
     ///
     /// You can invalidate the reference by performing any world mutating action.
     pub unsafe fn get_first<T: Component>(&self, second: impl IdFetcher) -> Option<&'a T> {
-        //get ids
-        let first_id = id::<T>().retrieve_id(self.world);
-        let second_id = second.retrieve_id(self.world);
-        let pair_id = unsafe { ecs_make_pair(first_id, second_id) };
+        let pair_id = IdView::pair(self.world, id::<T>(), second).id();
         unsafe {
             let ptr = ecs_get_id(self.world.ptr(), self.entity_id, pair_id) as *const T;
             ptr.as_ref()
@@ -245,10 +264,7 @@ impl<'a> EntityView<'a> {
     ///
     /// You can invalidate the reference by performing any world mutating action.
     pub unsafe fn get_second<T: Component>(&self, first: impl IdFetcher) -> Option<&'a T> {
-        //get ids
-        let second_id = id::<T>().retrieve_id(self.world);
-        let first_id = first.retrieve_id(self.world);
-        let pair_id = unsafe { ecs_make_pair(first_id, second_id) };
+        let pair_id = IdView::pair(self.world, first, id::<T>()).id();
         unsafe {
             let ptr = ecs_get_id(self.world.ptr(), self.entity_id, pair_id) as *const T;
             ptr.as_ref()
@@ -268,16 +284,61 @@ impl<'a> EntityView<'a> {
         }
     }
 
+    /// Gets a component mutably from the entity, adding it first if it is missing.
+    ///
+    /// # Panics
+    ///
+    /// If the component is missing or inherited and has no [Default] constructor, see
+    /// [Self::add].
+    ///
+    /// # Safety
+    ///
+    /// You can invalidate the reference by performing any world mutating action.
+    pub unsafe fn ensure<T: Component>(&self) -> &'a mut T {
+        const {
+            if T::IS_TAG {
+                panic!("cannot ensure a tag component");
+            }
+        }
+        let comp_id = id::<T>().retrieve_id(self.world);
+        self.assert_ensurable(comp_id);
+        let ptr = unsafe { ecs_ensure_id(self.world.ptr(), self.entity_id, comp_id) } as *mut T;
+        self.world.resume_panic();
+        unsafe { &mut *ptr }
+    }
+
+    /// Panics if an id is a data component which cannot be added without data.
+    fn assert_constructible(&self, id: Entity) {
+        if !HookContext::is_constructible(self.world, id) {
+            panic!(
+                "cannot add {} without data, it has no Default constructor",
+                IdView::new(self.world, id)
+            );
+        }
+    }
+
+    /// Panics if an id the entity does not own cannot be added to it without data.
+    ///
+    /// Inherited components are copied from the base, see [Self::auto_override].
+    fn assert_ensurable(&self, id: Entity) {
+        if self.owns(id) {
+            return;
+        }
+        self.assert_constructible(id);
+        assert!(
+            !self.has(id) || HookContext::is_copyable(self.world, id),
+            "{} cannot be copied from the base, it is dropped but not cloned",
+            IdView::new(self.world, id)
+        );
+    }
+
     /// Gets a data pair mutably where first is the data from the entity.
     ///
     /// # Safety
     ///
     /// You can invalidate the reference by performing any world mutating action.
     pub unsafe fn get_first_mut<T: Component>(&self, second: impl IdFetcher) -> Option<&'a mut T> {
-        //get ids
-        let first_id = id::<T>().retrieve_id(self.world);
-        let second_id = second.retrieve_id(self.world);
-        let pair_id = unsafe { ecs_make_pair(first_id, second_id) };
+        let pair_id = IdView::pair(self.world, id::<T>(), second).id();
         unsafe {
             let ptr = ecs_get_mut_id(self.world.ptr(), self.entity_id, pair_id) as *mut T;
             ptr.as_mut()
@@ -290,16 +351,84 @@ impl<'a> EntityView<'a> {
     ///
     /// You can invalidate the reference by performing any world mutating action.
     pub unsafe fn get_second_mut<T: Component>(&self, first: impl IdFetcher) -> Option<&'a mut T> {
-        //get ids
-        let second_id = id::<T>().retrieve_id(self.world);
-        let first_id = first.retrieve_id(self.world);
-        let pair_id = unsafe { ecs_make_pair(first_id, second_id) };
+        let pair_id = IdView::pair(self.world, first, id::<T>()).id();
         unsafe {
             let ptr = ecs_get_mut_id(self.world.ptr(), self.entity_id, pair_id) as *mut T;
             ptr.as_mut()
         }
     }
 
+    /// Modifies a component of the entity in place, then notifies flecs, so that `OnSet`
+    /// observers and change detection see the write.
+    ///
+    /// The component is added first if it is missing. Structural changes made by `func` are
+    /// deferred until it returns.
+    ///
+    /// # Panics
+    ///
+    /// If the component is missing or inherited and has no [Default] constructor, see
+    /// [Self::add], or if it is borrowed, see [Self::get_ref].
+    pub fn modify<T: Component, R>(&self, func: impl FnOnce(&mut T) -> R) -> R {
+        let comp_id = id::<T>().retrieve_id(self.world);
+        self.modify_id(comp_id, func)
+    }
+
+    /// Modifies a data pair where first is the data, see [Self::modify].
+    pub fn modify_first<T: Component, R>(
+        &self,
+        second: impl IdFetcher,
+        func: impl FnOnce(&mut T) -> R,
+    ) -> R {
+        let pair_id = IdView::pair(self.world, id::<T>(), second).id();
+        self.modify_id(pair_id, func)
+    }
+
+    /// Modifies a data pair where second is the data, see [Self::modify].
+    pub fn modify_second<T: Component, R>(
+        &self,
+        first: impl IdFetcher,
+        func: impl FnOnce(&mut T) -> R,
+    ) -> R {
+        let pair_id = IdView::pair(self.world, first, id::<T>()).id();
+        self.modify_id(pair_id, func)
+    }
+
+    /// Modifies the data of an id, which must be of type `T`.
+    fn modify_id<T: Component, R>(&self, id: Entity, func: impl FnOnce(&mut T) -> R) -> R {
+        const {
+            if T::IS_TAG {
+                panic!("cannot modify a tag component");
+            }
+        }
+        //the data of a pair may be either of its elements, or none
+        let type_id = unsafe { ecs_get_typeid(self.world.ptr(), id) };
+        assert!(
+            type_id == id::<T>().retrieve_id(self.world),
+            "{} does not hold data of type {:?}",
+            IdView::new(self.world, id),
+            core::any::type_name::<T>()
+        );
+        let result = {
+            //borrowed first, so that a conflicting borrow leaves the entity as it is
+            let _borrow = Borrow::new(self.world, self.entity_id, id, true);
+            self.assert_ensurable(id);
+            let ptr = unsafe { ecs_ensure_id(self.world.ptr(), self.entity_id, id) } as *mut T;
+            func(unsafe { &mut *ptr })
+        };
+        self.modified(id);
+        result
+    }
+
+    /// Notifies flecs that a component or pair was written in place, e.g. through
+    /// [Self::get_mut].
+    ///
+    /// Invokes `OnSet` hooks and observers and marks the data as changed.
+    pub fn modified(&self, id: impl IdFetcher) {
+        let id = id.retrieve_id(self.world);
+        unsafe { ecs_modified_id(self.world.ptr(), self.entity_id, id) };
+        self.world.resume_panic();
+    }
+
     /// Checks whether entity has a component or pair.
     pub fn has(&self, id: impl IdFetcher) -> bool {
         let id = id.retrieve_id(self.world);
@@ -312,6 +441,7 @@ impl<'a> EntityView<'a> {
         unsafe {
             ecs_remove_id(self.world.ptr(), self.entity_id, id);
         }
+        self.world.resume_panic();
     }
 
     /// Enables/Disables component/pair of the entity.
@@ -329,6 +459,79 @@ impl<'a> EntityView<'a> {
     }
 }
 
+//------------------------------------------------------------------------------
+// PREFABS
+//------------------------------------------------------------------------------
+
+impl<'a> EntityView<'a> {
+    /// Makes the entity inherit from a base entity, usually a prefab.
+    ///
+    /// Components of the base are copied as by [World::instantiate].
+    pub fn is_a(&self, base: impl IdFetcher) {
+        self.add(IdView::pair(self.world, IsA, base).id());
+    }
+
+    /// Checks whether the entity owns a component or pair, instead of inheriting it.
+    pub fn owns(&self, id: impl IdFetcher) -> bool {
+        let id = id.retrieve_id(self.world);
+        unsafe { ecs_owns_id(self.world.ptr(), self.entity_id, id) }
+    }
+
+    /// Marks a component of a prefab to be copied to its instances, even if it is inherited.
+    ///
+    /// # Panics
+    ///
+    /// If the component is dropped, but has no copy hook, see [World::component].
+    pub fn auto_override(&self, id: impl IdFetcher) {
+        let id = id.retrieve_id(self.world);
+        assert!(
+            HookContext::is_copyable(self.world, id),
+            "{} cannot be copied to instances, it is dropped but not cloned",
+            IdView::new(self.world, id)
+        );
+        unsafe { ecs_add_id(self.world.ptr(), self.entity_id, ECS_AUTO_OVERRIDE | id) }
+        self.world.resume_panic();
+    }
+
+    /// Sets a component of a prefab and marks it to be copied to its instances.
+    ///
+    /// # Panics
+    ///
+    /// Same as [Self::auto_override].
+    pub fn set_auto_override<T: Component>(&self, data: T) {
+        self.auto_override(id::<T>());
+        self.set_comp(data);
+    }
+
+    /// Marks a child of a prefab as a slot of the prefab.
+    ///
+    /// Instances of the prefab get a pair `(slot, instance_child)`, so that the instantiated
+    /// child can be found by [Self::slot].
+    pub fn slot_of(&self, prefab: impl IdFetcher) {
+        let id = IdView::pair(self.world, SlotOf, prefab).id();
+        unsafe { ecs_add_id(self.world.ptr(), self.entity_id, id) }
+        self.world.resume_panic();
+    }
+
+    /// Finds the instantiated child of a slot of the prefab this entity is an instance of.
+    pub fn slot(&self, name: &CStr) -> Option<EntityView<'a>> {
+        let prefab = unsafe { ecs_get_target(self.world.ptr(), self.entity_id, ECS_IS_A, 0) };
+        if prefab == 0 {
+            return None;
+        }
+        let slot = self.world.view(prefab).lookup(name)?;
+        let target = unsafe { ecs_get_target(self.world.ptr(), self.entity_id, slot.entity_id, 0) };
+        if target == 0 {
+            None
+        } else {
+            Some(EntityView {
+                world: self.world,
+                entity_id: target,
+            })
+        }
+    }
+}
+
 //------------------------------------------------------------------------------
 // MISC
 //------------------------------------------------------------------------------
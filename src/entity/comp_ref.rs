@@ -0,0 +1,77 @@
+use std::{cell::UnsafeCell, marker::PhantomData};
+
+use flecs_ecs_sys::*;
+
+use crate::{
+    component::{
+        Component,
+        id::{IdFetcher, id},
+    },
+    world::World,
+};
+
+use super::{Entity, EntityView};
+
+/// Cached reference to a component of an entity, created by [EntityView::comp_ref].
+///
+/// Remembers where the component is stored, so getting it skips the lookups of
+/// [EntityView::get]. The cache is revalidated when the entity moves to another table.
+pub struct CompRef<'a, T: Component> {
+    world: &'a World,
+    inner: UnsafeCell<ecs_ref_t>,
+    id: Entity,
+    __m: PhantomData<fn() -> T>,
+}
+
+impl<'a, T: Component> CompRef<'a, T> {
+    /// Gets the entity the reference points to.
+    #[inline]
+    pub fn entity(&self) -> EntityView<'a> {
+        self.world.view(unsafe { (*self.inner.get()).entity })
+    }
+
+    /// Gets the component.
+    ///
+    /// # Safety
+    ///
+    /// You can invalidate the reference by performing any world mutating action.
+    #[inline]
+    pub unsafe fn get(&self) -> Option<&'a T> {
+        let ptr =
+            unsafe { ecs_ref_get_id(self.world.ptr(), self.inner.get(), self.id) } as *const T;
+        unsafe { ptr.as_ref() }
+    }
+
+    /// Gets the component mutably.
+    ///
+    /// # Safety
+    ///
+    /// You can invalidate the reference by performing any world mutating action.
+    #[inline]
+    pub unsafe fn get_mut(&mut self) -> Option<&'a mut T> {
+        let ptr =
+            unsafe { ecs_ref_get_id(self.world.ptr(), self.inner.get_mut(), self.id) } as *mut T;
+        unsafe { ptr.as_mut() }
+    }
+}
+
+impl<'a> EntityView<'a> {
+    /// Creates a cached reference to a component of the entity.
+    ///
+    /// The entity does not need to have the component yet.
+    pub fn comp_ref<T: Component>(&self) -> CompRef<'a, T> {
+        const {
+            if T::IS_TAG {
+                panic!("cannot reference a tag component");
+            }
+        }
+        let comp_id = id::<T>().retrieve_id(self.world);
+        let inner = unsafe { ecs_ref_init_id(self.world.ptr(), self.entity_id, comp_id) };
+        CompRef {
+            world: self.world,
+            inner: UnsafeCell::new(inner),
+            id: comp_id,
+            __m: PhantomData,
+        }
+    }
+}
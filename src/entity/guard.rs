@@ -0,0 +1,149 @@
+use std::{
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+    ptr::NonNull,
+};
+
+use flecs_ecs_sys::*;
+
+use crate::{
+    component::{
+        Component,
+        id::{IdFetcher, id},
+        id_view::IdView,
+    },
+    flecs::IsA,
+    world::{World, WorldContext},
+};
+
+use super::{Entity, EntityView};
+
+/// Borrow of a component, recorded in the world while it is alive.
+///
+/// The world is deferred meanwhile, so that structural changes cannot move the component.
+pub(crate) struct Borrow<'a> {
+    world: &'a World,
+    entity: Entity,
+    id: Entity,
+}
+
+impl<'a> Borrow<'a> {
+    /// Records a borrow.
+    ///
+    /// # Panics
+    ///
+    /// If the component is borrowed mutably, or at all when borrowing mutably.
+    pub(crate) fn new(world: &'a World, entity: Entity, id: Entity, mutable: bool) -> Self {
+        let ctx = unsafe { WorldContext::get_or_init(world.ptr()) };
+        if !ctx.borrow(entity, id, mutable) {
+            panic!(
+                "{} of entity {entity} is already borrowed{}",
+                IdView::new(world, id),
+                if mutable { "" } else { " mutably" }
+            );
+        }
+        unsafe { ecs_defer_begin(world.ptr()) };
+        Self { world, entity, id }
+    }
+}
+
+impl Drop for Borrow<'_> {
+    fn drop(&mut self) {
+        let ctx = unsafe { WorldContext::get_or_init(self.world.ptr()) };
+        ctx.release(self.entity, self.id);
+        //applies structural changes made meanwhile
+        unsafe { ecs_defer_end(self.world.ptr()) };
+        if !std::thread::panicking() {
+            self.world.resume_panic();
+        }
+    }
+}
+
+/// Shared access to a component, created by [EntityView::get_ref].
+pub struct CompGuard<'a, T: Component> {
+    _borrow: Borrow<'a>,
+    data: NonNull<T>,
+    __m: PhantomData<&'a T>,
+}
+
+impl<T: Component> Deref for CompGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { self.data.as_ref() }
+    }
+}
+
+/// Exclusive access to a component, created by [EntityView::get_mut_guard].
+pub struct CompGuardMut<'a, T: Component> {
+    _borrow: Borrow<'a>,
+    data: NonNull<T>,
+    __m: PhantomData<&'a mut T>,
+}
+
+impl<T: Component> Deref for CompGuardMut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { self.data.as_ref() }
+    }
+}
+
+impl<T: Component> DerefMut for CompGuardMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { self.data.as_mut() }
+    }
+}
+
+impl<'a> EntityView<'a> {
+    /// Gets a component from the entity, safely.
+    ///
+    /// Structural changes, such as adding or removing components, are deferred until the guard
+    /// is dropped, so the component stays in place. Borrows are tracked per entity and
+    /// component, the component may be inherited from a prefab. Queries cannot be iterated and
+    /// the world cannot progress while any guard is alive.
+    ///
+    /// # Panics
+    ///
+    /// If the component is borrowed mutably.
+    pub fn get_ref<T: Component>(&self) -> Option<CompGuard<'a, T>> {
+        const {
+            if T::IS_TAG {
+                panic!("cannot borrow a tag component");
+            }
+        }
+        let comp_id = id::<T>().retrieve_id(self.world);
+        let data = unsafe { ecs_get_id(self.world.ptr(), self.entity_id, comp_id) } as *mut T;
+        let data = NonNull::new(data)?;
+        //borrow it where it is stored
+        let owner = self.target_for(IsA, comp_id)?.entity_id;
+        Some(CompGuard {
+            _borrow: Borrow::new(self.world, owner, comp_id, false),
+            data,
+            __m: PhantomData,
+        })
+    }
+
+    /// Gets a component mutably from the entity, safely.
+    ///
+    /// Same as [Self::get_ref], but the component must be owned by the entity.
+    ///
+    /// # Panics
+    ///
+    /// If the component is borrowed.
+    pub fn get_mut_guard<T: Component>(&self) -> Option<CompGuardMut<'a, T>> {
+        const {
+            if T::IS_TAG {
+                panic!("cannot borrow a tag component");
+            }
+        }
+        let comp_id = id::<T>().retrieve_id(self.world);
+        let data = unsafe { ecs_get_mut_id(self.world.ptr(), self.entity_id, comp_id) } as *mut T;
+        let data = NonNull::new(data)?;
+        Some(CompGuardMut {
+            _borrow: Borrow::new(self.world, self.entity_id, comp_id, true),
+            data,
+            __m: PhantomData,
+        })
+    }
+}
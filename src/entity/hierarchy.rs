@@ -0,0 +1,132 @@
+use std::collections::VecDeque;
+
+use flecs_ecs_sys::*;
+
+use crate::{
+    c_types::ECS_CHILD_OF,
+    component::{id::IdFetcher, id_view::IdView},
+    flecs::{ChildOf, Wildcard},
+    world::World,
+};
+
+use super::{Entity, EntityView, iter::EntityIter};
+
+/// Order in which [EntityView::descendants] visits entities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Traversal {
+    /// Visits a child and all its descendants before its next sibling.
+    DepthFirst,
+    /// Visits all entities of a depth before going deeper.
+    BreadthFirst,
+}
+
+/// Iterator over all descendants of an entity, created by [EntityView::descendants].
+pub struct Descendants<'a> {
+    world: &'a World,
+    order: Traversal,
+    pending: VecDeque<Entity>,
+}
+
+impl<'a> Iterator for Descendants<'a> {
+    type Item = EntityView<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entity = match self.order {
+            Traversal::DepthFirst => self.pending.pop_back()?,
+            Traversal::BreadthFirst => self.pending.pop_front()?,
+        };
+        let children = self
+            .world
+            .view(entity)
+            .children()
+            .map(|child| child.entity_id);
+        match self.order {
+            //reversed, so that the first child is visited first
+            Traversal::DepthFirst => {
+                let children: Vec<_> = children.collect();
+                self.pending.extend(children.into_iter().rev());
+            }
+            Traversal::BreadthFirst => self.pending.extend(children),
+        }
+        Some(self.world.view(entity))
+    }
+}
+
+impl<'a> EntityView<'a> {
+    /// Gets the parent of the entity.
+    pub fn parent(&self) -> Option<EntityView<'a>> {
+        let parent = unsafe { ecs_get_target(self.world.ptr(), self.entity_id, ECS_CHILD_OF, 0) };
+        if parent == 0 {
+            None
+        } else {
+            Some(self.world.view(parent))
+        }
+    }
+
+    /// Makes the entity a child of a parent, replacing its previous parent.
+    pub fn child_of(&self, parent: impl IdFetcher) {
+        let id = IdView::pair(self.world, ChildOf, parent).id();
+        unsafe { ecs_add_id(self.world.ptr(), self.entity_id, id) }
+        self.world.resume_panic();
+    }
+
+    /// Removes the entity from its parent, making it a root entity.
+    pub fn remove_parent(&self) {
+        let id = IdView::pair(self.world, ChildOf, Wildcard).id();
+        unsafe { ecs_remove_id(self.world.ptr(), self.entity_id, id) }
+        self.world.resume_panic();
+    }
+
+    /// Iterates direct children of the entity.
+    ///
+    /// Changes to the world are deferred while iterating, see [World::entities_with].
+    pub fn children(&self) -> EntityIter<'a> {
+        let iter = unsafe { ecs_children(self.world.ptr(), self.entity_id) };
+        EntityIter::new(self.world, iter, ecs_children_next)
+    }
+
+    /// Iterates all descendants of the entity, not including the entity itself.
+    ///
+    /// The hierarchy must not be changed while iterating.
+    pub fn descendants(&self, order: Traversal) -> Descendants<'a> {
+        let mut descendants = Descendants {
+            world: self.world,
+            order,
+            pending: VecDeque::from([self.entity_id]),
+        };
+        //skip the entity itself
+        descendants.next();
+        descendants
+    }
+
+    /// Number of ancestors of the entity, 0 for root entities.
+    pub fn depth(&self) -> u32 {
+        unsafe { ecs_get_depth(self.world.ptr(), self.entity_id, ECS_CHILD_OF) as u32 }
+    }
+}
+
+impl World {
+    /// Calls a function with a parent as the scope, so that new entities are its children.
+    ///
+    /// The previous scope is restored afterwards, even if the function panics.
+    pub fn with_scope<R>(&self, parent: impl IdFetcher, func: impl FnOnce() -> R) -> R {
+        /// Restores the scope when dropped.
+        struct ScopeGuard<'a> {
+            world: &'a World,
+            previous: Entity,
+        }
+
+        impl Drop for ScopeGuard<'_> {
+            fn drop(&mut self) {
+                unsafe { ecs_set_scope(self.world.ptr(), self.previous) };
+            }
+        }
+
+        let parent = parent.retrieve_id(self);
+        let _guard = ScopeGuard {
+            world: self,
+            previous: unsafe { ecs_set_scope(self.ptr(), parent) },
+        };
+        func()
+    }
+}
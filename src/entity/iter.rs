@@ -0,0 +1,72 @@
+use flecs_ecs_sys::*;
+
+use crate::world::World;
+
+use super::EntityView;
+
+/// Function advancing an iterator to its next table.
+type NextFn = unsafe extern "C" fn(*mut ecs_iter_t) -> bool;
+
+/// Iterator over entities of an uncached flecs iterator, such as children of an entity.
+///
+/// Changes to the world are deferred while it is alive, since they would move entities between
+/// the tables being iterated. Can be dropped before it is exhausted, the flecs iterator is
+/// cleaned up and the changes are applied.
+pub struct EntityIter<'a> {
+    world: &'a World,
+    iter: ecs_iter_t,
+    next: NextFn,
+    /// Index of the next entity in the current table.
+    row: usize,
+    /// Is the iterator exhausted?
+    done: bool,
+}
+
+impl<'a> EntityIter<'a> {
+    /// Wraps a flecs iterator.
+    pub(crate) fn new(world: &'a World, iter: ecs_iter_t, next: NextFn) -> Self {
+        unsafe { ecs_defer_begin(world.ptr()) };
+        Self {
+            world,
+            iter,
+            next,
+            row: 0,
+            done: false,
+        }
+    }
+}
+
+impl<'a> Iterator for EntityIter<'a> {
+    type Item = EntityView<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        //move to the next table with entities
+        while self.row >= self.iter.count as usize {
+            if !unsafe { (self.next)(&mut self.iter) } {
+                self.done = true;
+                return None;
+            }
+            self.row = 0;
+        }
+        let entity = unsafe { *self.iter.entities.add(self.row) };
+        self.row += 1;
+        Some(self.world.view(entity))
+    }
+}
+
+impl<'a> Drop for EntityIter<'a> {
+    fn drop(&mut self) {
+        //finished iterators are cleaned up by flecs
+        if !self.done {
+            unsafe { ecs_iter_fini(&mut self.iter) };
+        }
+        //applies changes made meanwhile
+        unsafe { ecs_defer_end(self.world.ptr()) };
+        if !std::thread::panicking() {
+            self.world.resume_panic();
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use flecs_ecs_sys::*;
+
+use crate::component::{id::IdFetcher, id_view::IdView};
+
+use super::{Entity, EntityView};
+
+/// Iterator over targets of a relationship, created by [EntityView::targets].
+pub struct Targets<'a> {
+    entity: EntityView<'a>,
+    relationship: Entity,
+    index: i32,
+}
+
+impl<'a> Iterator for Targets<'a> {
+    type Item = EntityView<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let world = self.entity.world;
+        let target = unsafe {
+            ecs_get_target(
+                world.ptr(),
+                self.entity.entity_id,
+                self.relationship,
+                self.index,
+            )
+        };
+        if target == 0 {
+            return None;
+        }
+        self.index += 1;
+        Some(world.view(target))
+    }
+}
+
+impl<'a> EntityView<'a> {
+    /// Gets a target of a relationship, `index` selects between multiple targets.
+    pub fn target(&self, relationship: impl IdFetcher, index: usize) -> Option<EntityView<'a>> {
+        let relationship = relationship.retrieve_id(self.world);
+        let target =
+            unsafe { ecs_get_target(self.world.ptr(), self.entity_id, relationship, index as i32) };
+        if target == 0 {
+            None
+        } else {
+            Some(self.world.view(target))
+        }
+    }
+
+    /// Iterates all targets of a relationship.
+    pub fn targets(&self, relationship: impl IdFetcher) -> Targets<'a> {
+        Targets {
+            entity: *self,
+            relationship: relationship.retrieve_id(self.world),
+            index: 0,
+        }
+    }
+
+    /// Counts targets of a relationship.
+    pub fn relation_count(&self, relationship: impl IdFetcher) -> usize {
+        self.targets(relationship).count()
+    }
+
+    /// Finds the entity a component is obtained from by following a relationship.
+    ///
+    /// Returns the entity itself if it owns the component, or for example the prefab it inherits
+    /// the component from, when following [crate::flecs::IsA].
+    pub fn target_for(
+        &self,
+        relationship: impl IdFetcher,
+        id: impl IdFetcher,
+    ) -> Option<EntityView<'a>> {
+        let relationship = relationship.retrieve_id(self.world);
+        let id = id.retrieve_id(self.world);
+        let target =
+            unsafe { ecs_get_target_for_id(self.world.ptr(), self.entity_id, relationship, id) };
+        if target == 0 {
+            None
+        } else {
+            Some(self.world.view(target))
+        }
+    }
+
+    /// Iterates ids of the entity, i.e. its components, tags and pairs.
+    ///
+    /// The ids are copied up front, so the entity may be changed while iterating.
+    pub fn each_id(&self) -> impl Iterator<Item = IdView<'a>> + use<'a> {
+        let world = self.world;
+        let ids = match unsafe { ecs_get_type(world.ptr(), self.entity_id).as_ref() } {
+            Some(ty) if ty.count > 0 => {
+                unsafe { std::slice::from_raw_parts(ty.array, ty.count as usize) }.to_vec()
+            }
+            _ => Vec::new(),
+        };
+        ids.into_iter().map(move |id| IdView::new(world, id))
+    }
+}
@@ -0,0 +1,98 @@
+use std::{
+    ffi::c_void,
+    marker::PhantomData,
+    mem::ManuallyDrop,
+    ptr::{null, null_mut},
+};
+
+use flecs_ecs_sys::*;
+
+use crate::{
+    component::{Component, id::IdFetcher},
+    entity::Entity,
+    world::World,
+};
+
+/// Builder for emitting events to observers.
+///
+/// The event type must be registered as a component, its data is passed to observers as
+/// a payload, readable through [crate::query::iter::Iter::event_param].
+pub struct EventBuilder<'a, E: Component> {
+    pub(crate) world: &'a World,
+    pub(crate) event: Entity,
+    pub(crate) ids: Vec<Entity>,
+    pub(crate) entity: Entity,
+    pub(crate) __m: PhantomData<fn(E)>,
+}
+
+impl<'a, E: Component> EventBuilder<'a, E> {
+    /// Adds a component or a pair the event is emitted for.
+    ///
+    /// Only observers with matching terms receive the event.
+    pub fn id(mut self, id: impl IdFetcher) -> Self {
+        self.ids.push(id.retrieve_id(self.world));
+        self
+    }
+
+    /// Sets the entity the event is emitted for.
+    pub fn entity(mut self, entity: impl IdFetcher) -> Self {
+        self.entity = entity.retrieve_id(self.world);
+        self
+    }
+
+    /// Emits the event immediately.
+    pub fn emit(self, payload: E) {
+        let mut payload = payload;
+        let param = if E::IS_TAG {
+            null_mut()
+        } else {
+            &mut payload as *mut E as *mut c_void
+        };
+        self.send(param, false);
+        //observers only borrowed the payload
+        drop(payload);
+    }
+
+    /// Enqueues the event, it is emitted once the world leaves the deferred mode.
+    ///
+    /// Emits immediately when not deferred.
+    pub fn enqueue(self, payload: E) {
+        //flecs only takes over the payload when deferred
+        if !unsafe { ecs_is_deferred(self.world.ptr()) } {
+            self.emit(payload);
+            return;
+        }
+        //flecs moves the payload into its queue and drops it after emitting
+        let mut payload = ManuallyDrop::new(payload);
+        let param = if E::IS_TAG {
+            null_mut()
+        } else {
+            &mut *payload as *mut E as *mut c_void
+        };
+        self.send(param, true);
+    }
+
+    /// Passes the event to flecs.
+    fn send(mut self, param: *mut c_void, enqueue: bool) {
+        assert!(!self.ids.is_empty(), "an event needs at least one id");
+        assert!(self.entity != 0, "an event needs an entity");
+        let ids = ecs_type_t {
+            array: self.ids.as_mut_ptr(),
+            count: self.ids.len() as i32,
+        };
+        let mut desc = ecs_event_desc_t {
+            event: self.event,
+            ids: &ids as *const _,
+            entity: self.entity,
+            param,
+            const_param: null(),
+            ..Default::default()
+        };
+        if enqueue {
+            unsafe { ecs_enqueue(self.world.ptr(), &mut desc as *mut _) };
+        } else {
+            unsafe { ecs_emit(self.world.ptr(), &mut desc as *mut _) };
+            self.world.resume_panic();
+        }
+    }
+}
@@ -1,7 +1,16 @@
+//allows the derive macros to refer to this crate by name
+extern crate self as simple_flecs;
+
+pub mod bundle;
 mod c_types;
 pub mod component;
 pub mod entity;
+pub mod event;
 pub mod flecs;
+pub mod module;
+pub mod observer;
+#[cfg(feature = "plugin")]
+pub mod plugin;
 pub mod prelude;
 pub mod query;
 pub mod system;
@@ -0,0 +1,99 @@
+//! Modules, grouping components, systems and observers under a module entity.
+//!
+//! Modules are imported with [World::import]. Everything a module creates in [Module::import] is
+//! scoped under its module entity, whose path is derived from [Module::NAME] the way flecs does
+//! for C modules, so `GameMovement` becomes `game.movement`.
+
+use std::{
+    any::TypeId,
+    cell::Cell,
+    ffi::CStr,
+    marker::PhantomData,
+    mem::ManuallyDrop,
+    ptr::{NonNull, null_mut},
+};
+
+use flecs_ecs_sys::*;
+
+use crate::{
+    entity::EntityView,
+    world::{ComponentMap, World, catch_panic},
+};
+
+/// Module which can be imported into a world.
+///
+/// ```ignore
+/// struct Movement;
+///
+/// impl Module for Movement {
+///     const NAME: &'static CStr = c"GameMovement";
+///
+///     fn import(world: &mut World) {
+///         world.register::<Position>();
+///         world.system().with(id::<Position>()).build(|iter| { /* ... */ });
+///     }
+/// }
+///
+/// world.import::<Movement>();
+/// ```
+pub trait Module: 'static {
+    /// Name of the module in C style, also the symbol of its module entity.
+    const NAME: &'static CStr;
+
+    /// Registers components, systems and observers of the module.
+    ///
+    /// Called once per world, with the module entity as the current scope.
+    fn import(world: &mut World);
+}
+
+/// Key of the module entity of `M` in the component map, distinct from the component `M`.
+struct ModuleKey<M>(PhantomData<M>);
+
+thread_local! {
+    /// Component map of the world importing a module, flecs gives the module action no context.
+    static IMPORTING_MAP: Cell<*mut ComponentMap> = const { Cell::new(null_mut()) };
+}
+
+impl World {
+    /// Imports a module, unless it is already imported.
+    ///
+    /// Returns the module entity.
+    pub fn import<M: Module>(&mut self) -> EntityView<'_> {
+        //modules may import other modules
+        let previous = IMPORTING_MAP.replace(self.component_map.as_ptr());
+        let module = unsafe { ecs_import(self.ptr(), Some(import_action::<M>), M::NAME.as_ptr()) };
+        IMPORTING_MAP.set(previous);
+        self.resume_panic();
+        assert!(module != 0, "failed to import a module");
+        //its symbol is its path, which flecs derives from the name
+        unsafe { self.component_map.as_ref() }
+            .borrow_mut()
+            .insert(TypeId::of::<ModuleKey<M>>(), module);
+        self.view(module)
+    }
+
+    /// Retrieves the module entity of a module imported by this world.
+    pub fn module<M: Module>(&self) -> Option<EntityView<'_>> {
+        let module = unsafe { self.component_map.as_ref() }
+            .borrow()
+            .get(&TypeId::of::<ModuleKey<M>>())
+            .copied()?;
+        unsafe { ecs_is_alive(self.ptr(), module) }.then(|| self.view(module))
+    }
+}
+
+/// Module action called by `ecs_import`.
+unsafe extern "C" fn import_action<M: Module>(world: *mut ecs_world_t) {
+    //create the module entity and scope everything under it
+    let desc = ecs_component_desc_t::default();
+    let module = unsafe { ecs_module_init(world, M::NAME.as_ptr(), &desc as *const _) };
+    unsafe { ecs_set_scope(world, module) };
+    let component_map = NonNull::new(IMPORTING_MAP.get()).expect("module imported without a world");
+    unsafe {
+        catch_panic(world, (), || {
+            let mut world =
+                ManuallyDrop::new(World::from_ptr_and_map(world, component_map.as_ptr()));
+            M::import(&mut world);
+        })
+    };
+}
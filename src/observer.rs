@@ -0,0 +1,202 @@
+use std::{
+    ffi::{CStr, CString, c_void},
+    ptr::NonNull,
+};
+
+use flecs_ecs_sys::*;
+
+use crate::{
+    c_types::{ECS_DISABLED, ECS_QUERY_MATCH_DISABLED, ECS_QUERY_MATCH_PREFAB},
+    component::id::IdFetcher,
+    entity::Entity,
+    query::{
+        iter::{Iter, MaybeOwnedIter},
+        term::TermBuilder,
+    },
+    world::{ComponentMap, World, catch_panic, is_panicking},
+};
+
+/// Binding context for observers.
+///
+/// Observers may be invoked again from within their own callback, e.g. by setting the observed
+/// component, so the callback is only ever borrowed immutably.
+#[repr(C)]
+struct ObserverContext<F: Fn(Iter<true>)> {
+    /// This field must always be first!
+    component_map: *mut ComponentMap,
+    func: F,
+}
+
+/// Handle to an observer created in an ECS world.
+///
+/// The handle does not own the observer, dropping it keeps the observer alive.
+/// It must not outlive the world it was created in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Observer {
+    world_ptr: NonNull<ecs_world_t>,
+    entity_id: Entity,
+}
+
+impl Observer {
+    /// Gets observer's entity id.
+    #[inline]
+    pub fn id(&self) -> Entity {
+        self.entity_id
+    }
+
+    /// Enables the observer.
+    #[inline]
+    pub fn enable(&self) {
+        unsafe { ecs_enable(self.world_ptr.as_ptr(), self.entity_id, true) };
+    }
+
+    /// Disables the observer.
+    #[inline]
+    pub fn disable(&self) {
+        unsafe { ecs_enable(self.world_ptr.as_ptr(), self.entity_id, false) };
+    }
+
+    /// Is the observer enabled?
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        unsafe { !ecs_has_id(self.world_ptr.as_ptr(), self.entity_id, ECS_DISABLED) }
+    }
+
+    /// Deletes the observer.
+    pub fn delete(self) {
+        unsafe { ecs_delete(self.world_ptr.as_ptr(), self.entity_id) };
+    }
+}
+
+/// Builder for creating observers. Allows you to set the events and the components to observe.
+#[derive(Debug)]
+pub struct ObserverBuilder<'a> {
+    pub(crate) inner: ecs_observer_desc_t,
+    pub(crate) expr: Option<CString>,
+    pub(crate) event_count: usize,
+    pub(crate) term_count: usize,
+    pub(crate) world: &'a World,
+}
+
+impl<'a> TermBuilder for ObserverBuilder<'a> {
+    fn term_world(&self) -> &World {
+        self.world
+    }
+
+    fn query_desc(&mut self) -> &mut ecs_query_desc_t {
+        &mut self.inner.query
+    }
+
+    fn term_count(&mut self) -> &mut usize {
+        &mut self.term_count
+    }
+}
+
+impl<'a> ObserverBuilder<'a> {
+    /// Sets an expression as the base for the query.
+    ///
+    /// This allocates a string, due to lifetimes.
+    pub fn expression(mut self, expr: &CStr) -> Self {
+        self.expr = Some(expr.to_owned());
+        self.inner.query.expr = self.expr.as_ref().unwrap().as_ptr();
+        self
+    }
+
+    /// Adds an event to observe, for example [crate::flecs::OnSet].
+    pub fn event(mut self, event: impl IdFetcher) -> Self {
+        let event = event.retrieve_id(self.world);
+        assert!(
+            self.event_count < self.inner.events.len(),
+            "an observer can have at most {} events",
+            self.inner.events.len()
+        );
+        self.inner.events[self.event_count] = event;
+        self.event_count += 1;
+        self
+    }
+
+    /// Invokes the observer for already matching entities when it is created.
+    pub fn yield_existing(mut self) -> Self {
+        self.inner.yield_existing = true;
+        self
+    }
+
+    /// Sets query to match prefabs.
+    pub fn match_prefabs(mut self) -> Self {
+        self.inner.query.flags |= ECS_QUERY_MATCH_PREFAB as u32;
+        self
+    }
+
+    /// Sets query to match disabled.
+    pub fn match_disabled(mut self) -> Self {
+        self.inner.query.flags |= ECS_QUERY_MATCH_DISABLED as u32;
+        self
+    }
+
+    /// Finishes this observer with default callback.
+    pub fn build<F>(self, callback: F) -> Observer
+    where
+        F: Fn(Iter<true>) + 'static,
+    {
+        let entity = self.world.entity().id();
+        self.finish(entity, callback)
+    }
+
+    /// Finishes this observer with default callback.
+    pub fn build_named<F>(self, name: &CStr, callback: F) -> Observer
+    where
+        F: Fn(Iter<true>) + 'static,
+    {
+        let entity = self.world.entity_named(name).id();
+        self.finish(entity, callback)
+    }
+
+    /// Creates the observer on an entity.
+    fn finish<F>(mut self, entity: Entity, callback: F) -> Observer
+    where
+        F: Fn(Iter<true>) + 'static,
+    {
+        assert!(self.event_count > 0, "an observer needs at least one event");
+        //set callback
+        self.inner.callback = Some(observer_callback::<F>);
+        self.inner.callback_ctx = Box::leak(Box::new(ObserverContext {
+            component_map: self.world.component_map.as_ptr(),
+            func: callback,
+        })) as *mut _ as *mut c_void;
+        self.inner.callback_ctx_free = Some(observer_ctx_free::<F>);
+        //sets the entity
+        self.inner.entity = entity;
+        //creates the observer
+        let entity_id = unsafe { ecs_observer_init(self.world.ptr(), &self.inner as *const _) };
+        assert!(entity_id != 0, "failed to create an observer");
+        Observer {
+            world_ptr: self.world.ptr,
+            entity_id,
+        }
+    }
+}
+
+unsafe extern "C" fn observer_callback<F: Fn(Iter<true>) + 'static>(iter: *mut ecs_iter_t) {
+    //skip the rest of the frame after a panic
+    let world = unsafe { (*iter).world };
+    if unsafe { is_panicking(world) } {
+        return;
+    }
+    //retrieve the function, shared since observers are re-entrant
+    let context = unsafe { (*iter).callback_ctx as *const ObserverContext<F> };
+    let context = unsafe { context.as_ref().unwrap() };
+    //create iterator
+    let iter = Iter::<true> {
+        iter: MaybeOwnedIter::Ptr(NonNull::new(iter).unwrap()),
+        //observers match events, not tables of a cache
+        cached: false,
+    };
+    //call callback, panics must not unwind into flecs
+    unsafe { catch_panic(world, (), || (context.func)(iter)) };
+}
+
+unsafe extern "C" fn observer_ctx_free<F: Fn(Iter<true>) + 'static>(ctx: *mut c_void) {
+    // SAFETY:
+    // ctx is a pointer to ObserverContext<F>, so we can safely cast it back.
+    let _ = unsafe { Box::from_raw(ctx as *mut ObserverContext<F>) };
+}
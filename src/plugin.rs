@@ -0,0 +1,542 @@
+//! Loading of plugins from dynamic libraries sharing one world.
+//!
+//! A plugin is a `cdylib` exporting a [PluginDescriptor] and an init function, both generated by
+//! [crate::export_plugin]. Everything the plugin creates during its init is scoped under a plugin
+//! module entity, which is how the loader knows what to delete when unloading it.
+//!
+//! A plugin can be reloaded after its library was rebuilt, see [Plugin::reload]. Its systems,
+//! observers and queries are recreated, while entities keep the data of components whose layout
+//! did not change.
+
+use std::{
+    ffi::{CStr, CString, OsStr, c_char, c_void},
+    fmt, fs, io,
+    panic::resume_unwind,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use flecs_ecs_sys::*;
+use libloading::{Library, Symbol};
+
+use crate::{
+    c_types::{
+        ECS_CHILD_OF, ECS_COMPONENT, ECS_MODULE, ECS_OBSERVER, ECS_POLY, ECS_QUERY, ECS_SYSTEM,
+    },
+    component::hooks::HookContext,
+    entity::{Entity, hierarchy::Traversal},
+    world::{ComponentMap, World, WorldContext, catch_panic},
+};
+
+/// Version of the plugin ABI, bumped on every incompatible change.
+pub const PLUGIN_ABI_VERSION: u32 = 3;
+
+/// Name of the exported descriptor.
+pub const PLUGIN_DESCRIPTOR_SYMBOL: &CStr = c"simple_flecs_plugin_descriptor";
+
+/// Name of the exported init function.
+pub const PLUGIN_INIT_SYMBOL: &CStr = c"simple_flecs_plugin_init";
+
+/// Name of the exported fini function.
+pub const PLUGIN_FINI_SYMBOL: &CStr = c"simple_flecs_plugin_fini";
+
+/// Signature of the plugin init function, returns the state of the plugin.
+pub type PluginInitFn = unsafe extern "C" fn(world: *mut ecs_world_t) -> *mut c_void;
+
+/// Signature of the plugin fini function, frees the state once nothing of the plugin is left.
+pub type PluginFiniFn = unsafe extern "C" fn(state: *mut c_void);
+
+/// Descriptor exported by every plugin, checked before the plugin is initialized.
+#[repr(C)]
+#[derive(Debug)]
+pub struct PluginDescriptor {
+    /// [PLUGIN_ABI_VERSION] the plugin was built with.
+    pub abi_version: u32,
+    /// Fingerprint of the flecs structures the plugin was built with.
+    pub layout_hash: u64,
+    /// Fingerprint of the world and hook contexts the plugin was built with.
+    pub context_hash: u64,
+    /// Name of the plugin, also the name of its module entity.
+    pub name: *const c_char,
+}
+
+// SAFETY:
+// The name points to a static string.
+unsafe impl Sync for PluginDescriptor {}
+
+impl PluginDescriptor {
+    /// Creates a descriptor for the current build.
+    pub const fn new(name: &'static CStr) -> Self {
+        Self {
+            abi_version: PLUGIN_ABI_VERSION,
+            layout_hash: flecs_layout_hash(),
+            context_hash: context_layout_hash(),
+            name: name.as_ptr(),
+        }
+    }
+}
+
+/// Fingerprint of flecs structures shared between the host and its plugins.
+pub const fn flecs_layout_hash() -> u64 {
+    let sizes = [
+        size_of::<ecs_iter_t>(),
+        size_of::<ecs_type_hooks_t>(),
+        size_of::<ecs_type_info_t>(),
+        size_of::<ecs_term_t>(),
+        size_of::<ecs_query_desc_t>(),
+        size_of::<ecs_system_desc_t>(),
+        size_of::<ecs_observer_desc_t>(),
+        size_of::<ecs_entity_desc_t>(),
+    ];
+    fnv(&sizes)
+}
+
+/// Fingerprint of the contexts shared between the host and its plugins through the world.
+///
+/// The host reads contexts created by plugins and the other way round, so they must agree on
+/// their layout.
+pub const fn context_layout_hash() -> u64 {
+    fnv(&[
+        WorldContext::VERSION as usize,
+        size_of::<WorldContext>(),
+        align_of::<WorldContext>(),
+        HookContext::MAGIC as usize,
+        size_of::<HookContext>(),
+        align_of::<HookContext>(),
+    ])
+}
+
+/// Hashes sizes with fnv-1a.
+const fn fnv(sizes: &[usize]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let mut i = 0;
+    while i < sizes.len() {
+        hash ^= sizes[i] as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+        i += 1;
+    }
+    hash
+}
+
+/// Exports a plugin from a `cdylib`.
+///
+/// The init function receives a world attached to the host world. Its component map lives as
+/// long as the plugin, since systems and observers of the plugin refer to it. Panics of the init
+/// function are resumed by the loader.
+///
+/// ```ignore
+/// fn init(world: &mut World) {
+///     world.register::<Score>();
+/// }
+///
+/// simple_flecs::export_plugin!(c"score_plugin", init);
+/// ```
+#[macro_export]
+macro_rules! export_plugin {
+    ($name:expr, $init:path) => {
+        #[unsafe(no_mangle)]
+        #[allow(non_upper_case_globals)]
+        pub static simple_flecs_plugin_descriptor: $crate::plugin::PluginDescriptor =
+            $crate::plugin::PluginDescriptor::new($name);
+
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn simple_flecs_plugin_init(
+            world: *mut $crate::sys::ecs_world_t,
+        ) -> *mut ::core::ffi::c_void {
+            unsafe { $crate::plugin::init_plugin(world, $init) }
+        }
+
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn simple_flecs_plugin_fini(state: *mut ::core::ffi::c_void) {
+            unsafe { $crate::plugin::fini_plugin(state) }
+        }
+    };
+}
+
+/// Initializes a plugin, called by the init function generated by [crate::export_plugin].
+///
+/// The component map of the plugin is returned as its state, it is freed by [fini_plugin] once
+/// the systems and observers referring to it are deleted. A panic of `init` is stored in the
+/// world.
+///
+/// # Safety
+///
+/// The pointer must be a valid pointer to the world of the loader.
+#[doc(hidden)]
+pub unsafe fn init_plugin(world: *mut ecs_world_t, init: fn(&mut World)) -> *mut c_void {
+    //the flecs copy of the plugin has to be initialized too
+    if unsafe { ecs_os_get_api() }.malloc_.is_none() {
+        unsafe { ecs_os_set_api_defaults() };
+    }
+    let component_map = Box::into_raw(Box::<ComponentMap>::default());
+    let mut plugin_world = unsafe { World::from_ptr_and_map(world, component_map) };
+    unsafe { catch_panic(world, (), || init(&mut plugin_world)) };
+    component_map as *mut c_void
+}
+
+/// Frees the state of a plugin, called by the fini function generated by
+/// [crate::export_plugin].
+///
+/// # Safety
+///
+/// The state must be returned by [init_plugin] and nothing may refer to it anymore.
+#[doc(hidden)]
+pub unsafe fn fini_plugin(state: *mut c_void) {
+    drop(unsafe { Box::from_raw(state as *mut ComponentMap) });
+}
+
+/// Error of loading a plugin.
+#[derive(Debug)]
+pub enum PluginError {
+    /// The library could not be copied for a reload.
+    Copy(io::Error),
+    /// The library could not be loaded.
+    Library(libloading::Error),
+    /// The library does not export a plugin.
+    NotAPlugin(libloading::Error),
+    /// The plugin was built for another ABI version.
+    AbiVersion { expected: u32, found: u32 },
+    /// The plugin was built against different flecs structures.
+    Layout { expected: u64, found: u64 },
+    /// The plugin was built with different world contexts.
+    Context { expected: u64, found: u64 },
+}
+
+impl fmt::Display for PluginError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PluginError::Copy(err) => write!(f, "failed to copy the plugin library: {err}"),
+            PluginError::Library(err) => write!(f, "failed to load the plugin library: {err}"),
+            PluginError::NotAPlugin(err) => write!(f, "library is not a plugin: {err}"),
+            PluginError::AbiVersion { expected, found } => write!(
+                f,
+                "plugin was built for ABI version {found}, expected {expected}"
+            ),
+            PluginError::Layout { expected, found } => write!(
+                f,
+                "plugin was built against different flecs structures ({found:#x}, expected {expected:#x})"
+            ),
+            PluginError::Context { expected, found } => write!(
+                f,
+                "plugin was built with different world contexts ({found:#x}, expected {expected:#x})"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PluginError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PluginError::Copy(err) => Some(err),
+            PluginError::Library(err) | PluginError::NotAPlugin(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Plugin loaded into a world.
+///
+/// Must be unloaded by [Plugin::unload] before the world is dropped. A plugin dropped without
+/// unloading keeps its library loaded forever, since the world may still call into it.
+#[derive(Debug)]
+pub struct Plugin {
+    name: CString,
+    path: PathBuf,
+    scope: Entity,
+    library: Option<Library>,
+    /// Copy of the library loaded by the last reload.
+    copy: Option<PathBuf>,
+    /// State returned by the init function of the library.
+    state: *mut c_void,
+    /// Frees the state.
+    fini: PluginFiniFn,
+}
+
+impl Plugin {
+    /// Loads a plugin library and initializes it in the world.
+    ///
+    /// # Panics
+    ///
+    /// If the init function of the plugin panics, the plugin is unloaded first.
+    ///
+    /// # Safety
+    ///
+    /// Loading a library runs its initialization code, the library must be a trusted plugin.
+    pub unsafe fn load(world: &World, path: impl AsRef<OsStr>) -> Result<Plugin, PluginError> {
+        let path = PathBuf::from(path.as_ref());
+        let (library, name, init, fini) = unsafe { open(&path) }?;
+        //create the module entity of the plugin
+        let scope = world.entity_named(&name).id();
+        unsafe { ecs_add_id(world.ptr(), scope, ECS_MODULE) };
+        //initialize the plugin inside its scope
+        let state = unsafe { init_in_scope(world, scope, init) };
+        let plugin = Plugin {
+            name,
+            path,
+            scope,
+            library: Some(library),
+            copy: None,
+            state,
+            fini,
+        };
+        if let Some(payload) = world.take_panic() {
+            plugin.unload(world);
+            resume_unwind(payload);
+        }
+        Ok(plugin)
+    }
+
+    /// Reloads the plugin from the path it was loaded from, without tearing down the world.
+    ///
+    /// The init of the new version runs inside the same scope, then systems, observers and
+    /// queries of the old version are deleted. Components keep their ids and data if the new
+    /// version registers them with the same layout, others are deleted together with their data.
+    /// The old library is unloaded last, after nothing refers to it anymore.
+    ///
+    /// On error, the old version keeps running.
+    ///
+    /// # Panics
+    ///
+    /// If the init function of the new version panics. Its systems, observers and queries are
+    /// deleted and the old ones keep running, while components it registered keep its hooks, so
+    /// the new library stays loaded.
+    ///
+    /// # Safety
+    ///
+    /// Same as [Self::load]. The world must not be iterated during the reload.
+    pub unsafe fn reload(&mut self, world: &World) -> Result<(), PluginError> {
+        //the loader hands out the old library for a path it has already loaded
+        let copy = copy_library(&self.path)?;
+        let (library, _, init, fini) = match unsafe { open(&copy) } {
+            Ok(opened) => opened,
+            Err(err) => {
+                let _ = fs::remove_file(&copy);
+                return Err(err);
+            }
+        };
+        //the new callbacks may take the names of the old ones
+        let (retired, parents) = self.retire_callbacks(world);
+        //initialize the new version, recording the components it registers
+        let old_components = self.components(world);
+        let ctx = unsafe { WorldContext::get_or_init(world.ptr()) };
+        ctx.begin_reload();
+        let state = unsafe { init_in_scope(world, self.scope, init) };
+        let reloaded = ctx.end_reload();
+        if let Some(payload) = world.take_panic() {
+            //bring the old callbacks back
+            self.delete_callbacks(world);
+            for (entity, parent) in parents {
+                unsafe { ecs_add_id(world.ptr(), entity, ecs_make_pair(ECS_CHILD_OF, parent)) };
+            }
+            unsafe { ecs_delete(world.ptr(), retired) };
+            std::mem::forget(library);
+            let _ = fs::remove_file(&copy);
+            resume_unwind(payload);
+        }
+        //delete the old callbacks together with their entity
+        unsafe { ecs_delete(world.ptr(), retired) };
+        //components the new version no longer registers
+        for entity in old_components {
+            //a component with a changed layout is already deleted
+            if !reloaded.contains(&entity) && unsafe { ecs_is_alive(world.ptr(), entity) } {
+                unsafe { ecs_delete(world.ptr(), entity) };
+            }
+        }
+        //nothing refers to the state of the old version anymore
+        unsafe { (self.fini)(self.state) };
+        self.state = state;
+        self.fini = fini;
+        //swap the libraries
+        drop(self.library.replace(library));
+        if let Some(old) = self.copy.replace(copy) {
+            let _ = fs::remove_file(old);
+        }
+        world.resume_panic();
+        Ok(())
+    }
+
+    /// Name of the plugin.
+    #[inline]
+    pub fn name(&self) -> &CStr {
+        &self.name
+    }
+
+    /// Module entity everything created by the plugin is scoped under.
+    #[inline]
+    pub fn scope(&self) -> Entity {
+        self.scope
+    }
+
+    /// Systems created by the plugin.
+    pub fn systems(&self, world: &World) -> Vec<Entity> {
+        let system = unsafe { ecs_make_pair(ECS_POLY, ECS_SYSTEM) };
+        self.owned_with(world, system)
+    }
+
+    /// Observers created by the plugin.
+    pub fn observers(&self, world: &World) -> Vec<Entity> {
+        let observer = unsafe { ecs_make_pair(ECS_POLY, ECS_OBSERVER) };
+        self.owned_with(world, observer)
+    }
+
+    /// Queries created by the plugin.
+    pub fn queries(&self, world: &World) -> Vec<Entity> {
+        let query = unsafe { ecs_make_pair(ECS_POLY, ECS_QUERY) };
+        self.owned_with(world, query)
+    }
+
+    /// Components registered by the plugin.
+    pub fn components(&self, world: &World) -> Vec<Entity> {
+        self.owned_with(world, ECS_COMPONENT)
+    }
+
+    /// Unloads the plugin.
+    ///
+    /// Systems and observers are deleted first, then components, then the rest of the entities
+    /// of the plugin. The library is unloaded last.
+    pub fn unload(mut self, world: &World) {
+        self.delete_owned(world);
+        unsafe { (self.fini)(self.state) };
+        drop(self.library.take());
+        if let Some(copy) = self.copy.take() {
+            let _ = fs::remove_file(copy);
+        }
+    }
+
+    /// Deletes everything the plugin created.
+    pub(crate) fn delete_owned(&self, world: &World) {
+        //callbacks first, they may refer to components
+        self.delete_callbacks(world);
+        //components, their hooks live in the library
+        for entity in self.components(world) {
+            unsafe { ecs_delete(world.ptr(), entity) };
+        }
+        //the rest
+        unsafe { ecs_delete(world.ptr(), self.scope) };
+        world.resume_panic();
+    }
+
+    /// Systems, observers and queries of the plugin.
+    fn callbacks(&self, world: &World) -> Vec<Entity> {
+        let mut callbacks = self.systems(world);
+        callbacks.extend(self.observers(world));
+        callbacks.extend(self.queries(world));
+        callbacks
+    }
+
+    /// Deletes systems, observers and queries of the plugin.
+    fn delete_callbacks(&self, world: &World) {
+        for entity in self.callbacks(world) {
+            unsafe { ecs_delete(world.ptr(), entity) };
+        }
+    }
+
+    /// Moves systems, observers and queries of the plugin out of its scope, under a new entity.
+    ///
+    /// Returns the new entity and the previous parents of the moved entities.
+    fn retire_callbacks(&self, world: &World) -> (Entity, Vec<(Entity, Entity)>) {
+        let retired = world.entity().id();
+        let parents = self
+            .callbacks(world)
+            .into_iter()
+            .map(|entity| {
+                let parent = unsafe { ecs_get_target(world.ptr(), entity, ECS_CHILD_OF, 0) };
+                unsafe { ecs_add_id(world.ptr(), entity, ecs_make_pair(ECS_CHILD_OF, retired)) };
+                (entity, parent)
+            })
+            .collect();
+        (retired, parents)
+    }
+
+    /// Entities in the scope of the plugin with an id.
+    fn owned_with(&self, world: &World, id: Entity) -> Vec<Entity> {
+        world
+            .view(self.scope)
+            .descendants(Traversal::DepthFirst)
+            .filter(|entity| entity.has(id))
+            .map(|entity| entity.id())
+            .collect()
+    }
+}
+
+impl Drop for Plugin {
+    fn drop(&mut self) {
+        //the world may still call into the library
+        if let Some(library) = self.library.take() {
+            std::mem::forget(library);
+        }
+    }
+}
+
+/// Loads a plugin library and checks its descriptor.
+///
+/// # Safety
+///
+/// Same as [Plugin::load].
+unsafe fn open(path: &Path) -> Result<(Library, CString, PluginInitFn, PluginFiniFn), PluginError> {
+    let library = unsafe { Library::new(path) }.map_err(PluginError::Library)?;
+    //check the descriptor
+    let name = {
+        let descriptor: Symbol<*const PluginDescriptor> =
+            unsafe { library.get(PLUGIN_DESCRIPTOR_SYMBOL.to_bytes_with_nul()) }
+                .map_err(PluginError::NotAPlugin)?;
+        let descriptor = unsafe { &**descriptor };
+        if descriptor.abi_version != PLUGIN_ABI_VERSION {
+            return Err(PluginError::AbiVersion {
+                expected: PLUGIN_ABI_VERSION,
+                found: descriptor.abi_version,
+            });
+        }
+        if descriptor.layout_hash != flecs_layout_hash() {
+            return Err(PluginError::Layout {
+                expected: flecs_layout_hash(),
+                found: descriptor.layout_hash,
+            });
+        }
+        if descriptor.context_hash != context_layout_hash() {
+            return Err(PluginError::Context {
+                expected: context_layout_hash(),
+                found: descriptor.context_hash,
+            });
+        }
+        unsafe { CStr::from_ptr(descriptor.name) }.to_owned()
+    };
+    let init: Symbol<PluginInitFn> = unsafe { library.get(PLUGIN_INIT_SYMBOL.to_bytes_with_nul()) }
+        .map_err(PluginError::NotAPlugin)?;
+    let init = *init;
+    let fini: Symbol<PluginFiniFn> = unsafe { library.get(PLUGIN_FINI_SYMBOL.to_bytes_with_nul()) }
+        .map_err(PluginError::NotAPlugin)?;
+    let fini = *fini;
+    Ok((library, name, init, fini))
+}
+
+/// Runs the init function of a plugin inside its scope, returns the state of the plugin.
+///
+/// # Safety
+///
+/// The init function must come from a loaded plugin library.
+unsafe fn init_in_scope(world: &World, scope: Entity, init: PluginInitFn) -> *mut c_void {
+    //the context must not be created by the library, its free function would point into it
+    unsafe { WorldContext::get_or_init(world.ptr()) };
+    let previous = unsafe { ecs_set_scope(world.ptr(), scope) };
+    let state = unsafe { init(world.ptr()) };
+    unsafe { ecs_set_scope(world.ptr(), previous) };
+    state
+}
+
+/// Copies a library to a new path in the temporary directory.
+fn copy_library(path: &Path) -> Result<PathBuf, PluginError> {
+    static COPIES: AtomicUsize = AtomicUsize::new(0);
+    let file_name = path
+        .file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy();
+    let copy = std::env::temp_dir().join(format!(
+        "{}-{}-{file_name}",
+        process::id(),
+        COPIES.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::copy(path, &copy).map_err(PluginError::Copy)?;
+    Ok(copy)
+}
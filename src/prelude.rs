@@ -1,13 +1,23 @@
 //List of useful imports.
 
+pub use crate::bundle::Bundle;
 pub use crate::component::Component;
 pub use crate::component::ComponentView;
+pub use crate::component::hooks::ComponentHooks;
 pub use crate::component::id::id;
+pub use crate::component::id_view::IdView;
 pub use crate::entity::Entity;
 pub use crate::entity::EntityView;
+pub use crate::entity::comp_ref::CompRef;
+pub use crate::module::Module;
+pub use crate::observer::ObserverBuilder;
 pub use crate::query::Query;
 pub use crate::query::QueryBuilder;
 pub use crate::query::iter::Iter;
+pub use crate::query::term::TermBuilder;
+pub use crate::query::typed::TypedQuery;
+pub use crate::query::{InOutKind, OperKind, QueryCacheKind};
+pub use crate::system::System;
 pub use crate::system::SystemBuilder;
 pub use crate::world::World;
 
@@ -1,5 +1,7 @@
 pub mod callbacks;
 pub mod iter;
+pub mod term;
+pub mod typed;
 
 use std::{
     ffi::{CStr, CString, c_void},
@@ -9,6 +11,7 @@ use std::{
 use callbacks::OrderByFunc;
 use flecs_ecs_sys::*;
 use iter::Iter;
+use term::TermBuilder;
 
 use crate::{
     c_types::{
@@ -20,17 +23,21 @@ use crate::{
         id::{IdFetcher, id},
     },
     entity::Entity,
-    world::{ComponentMap, World},
+    world::{ComponentMap, World, assert_unborrowed},
 };
 
+pub use crate::c_types::{InOutKind, OperKind, QueryCacheKind};
+
 /// Wrapper around a query in an ECS world.
 ///
 /// The query must not out live the world it was created in.
 pub struct Query {
-    world_ptr: NonNull<ecs_world_t>,
-    component_map: NonNull<ComponentMap>,
-    query: NonNull<ecs_query_t>,
-    entity_id: Option<Entity>,
+    pub(crate) world_ptr: NonNull<ecs_world_t>,
+    pub(crate) component_map: NonNull<ComponentMap>,
+    pub(crate) query: NonNull<ecs_query_t>,
+    pub(crate) entity_id: Option<Entity>,
+    /// Cache kind the query was created with.
+    pub(crate) cache_kind: QueryCacheKind,
 }
 
 impl Drop for Query {
@@ -50,13 +57,38 @@ impl Drop for Query {
 
 impl Query {
     /// Begin query iteration.
+    ///
+    /// # Panics
+    ///
+    /// If a component is borrowed by a guard, see [crate::entity::EntityView::get_ref].
     pub fn iter(&self) -> Iter<false> {
+        unsafe { assert_unborrowed(self.world_ptr.as_ptr(), "iterate a query") };
         let mut iter = unsafe { ecs_query_iter(self.world_ptr.as_ptr(), self.query.as_ptr()) };
         iter.binding_ctx = self.component_map.as_ptr() as *mut c_void;
         Iter {
             iter: iter::MaybeOwnedIter::Owned(iter),
+            cached: self.is_cached(),
         }
     }
+
+    /// Checks whether data the query reads changed since the last call, or since the query was
+    /// created.
+    ///
+    /// Data changes through `set_comp`, [crate::entity::EntityView::modified] and iterations of
+    /// queries writing to it. Must not be called while iterating the query.
+    ///
+    /// # Panics
+    ///
+    /// If the query is not cached, see [QueryBuilder::set_cache].
+    pub fn changed(&self) -> bool {
+        assert!(self.is_cached(), "change detection requires a cached query");
+        unsafe { ecs_query_changed(self.query.as_ptr()) }
+    }
+
+    /// Is the query cached?
+    fn is_cached(&self) -> bool {
+        self.cache_kind.is_auto() || self.cache_kind.is_all()
+    }
 }
 
 /// Builder for creating queries. Allows you to set certain flags and the components to request.
@@ -64,9 +96,24 @@ impl Query {
 pub struct QueryBuilder<'a> {
     pub(crate) inner: ecs_query_desc_t,
     pub(crate) expr: Option<CString>,
+    pub(crate) term_count: usize,
     pub(crate) world: &'a World,
 }
 
+impl<'a> TermBuilder for QueryBuilder<'a> {
+    fn term_world(&self) -> &World {
+        self.world
+    }
+
+    fn query_desc(&mut self) -> &mut ecs_query_desc_t {
+        &mut self.inner
+    }
+
+    fn term_count(&mut self) -> &mut usize {
+        &mut self.term_count
+    }
+}
+
 impl<'a> QueryBuilder<'a> {
     /// Sets an expression as the base for the query.
     ///
@@ -104,15 +151,7 @@ impl<'a> QueryBuilder<'a> {
     /// Orders output by a component.
     pub fn order_by<T: Component, F: OrderByFunc<T>>(mut self, callback: F) -> Self {
         self.inner.order_by = id::<T>().retrieve_id(self.world);
-        self.inner.order_by_callback = unsafe {
-            // SAFETY:
-            // Components are sized, so references are thin, though it is an evil hack.
-            let extern_fun = std::mem::transmute::<
-                extern "C" fn(u64, &T, u64, &T) -> i32,
-                unsafe extern "C" fn(u64, *const c_void, u64, *const c_void) -> i32,
-            >(callback.to_extern());
-            Some(extern_fun)
-        };
+        self.inner.order_by_callback = Some(callback.to_extern());
         self
     }
 
@@ -124,6 +163,7 @@ impl<'a> QueryBuilder<'a> {
             query: NonNull::new(query_ptr).unwrap(),
             component_map: self.world.component_map,
             entity_id: None,
+            cache_kind: self.inner.cache_kind.into(),
         }
     }
 
@@ -136,6 +176,7 @@ impl<'a> QueryBuilder<'a> {
             query: NonNull::new(query_ptr).unwrap(),
             component_map: self.world.component_map,
             entity_id,
+            cache_kind: self.inner.cache_kind.into(),
         }
     }
 
@@ -148,6 +189,7 @@ impl<'a> QueryBuilder<'a> {
             query: NonNull::new(query_ptr).unwrap(),
             component_map: self.world.component_map,
             entity_id,
+            cache_kind: self.inner.cache_kind.into(),
         }
     }
 }
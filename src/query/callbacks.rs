@@ -1,12 +1,62 @@
-use crate::{component::Component, entity::Entity};
+use std::{ffi::c_void, ptr::NonNull};
+
+use crate::{component::Component, entity::Entity, world::catch_orphan_panic};
+
+/// Order by callback as called by flecs.
+pub type ExternOrderByFunc =
+    unsafe extern "C" fn(Entity, *const c_void, Entity, *const c_void) -> i32;
 
 /// Trait implemented by functions suitable to be used to order query results.
-pub trait OrderByFunc<T: Component> {
-    fn to_extern(self) -> extern "C" fn(Entity, &T, Entity, &T) -> i32;
+///
+/// Implemented for functions and closures without captures, flecs gives the callback no context
+/// to store captures in. Panics of the callback are caught and resumed after the query
+/// iteration or frame.
+///
+/// Also implemented for `extern "C"` function pointers, which are handed to flecs as they are.
+/// Panics can not unwind out of those and abort.
+pub trait OrderByFunc<T: Component>: Copy + 'static {
+    /// Converts the function to a callback flecs can call.
+    fn to_extern(self) -> ExternOrderByFunc;
+}
+
+impl<T, F> OrderByFunc<T> for F
+where
+    T: Component,
+    F: Fn(Entity, &T, Entity, &T) -> i32 + Copy + 'static,
+{
+    fn to_extern(self) -> ExternOrderByFunc {
+        const {
+            if size_of::<F>() != 0 {
+                panic!("order_by callbacks must be functions or closures without captures");
+            }
+        }
+        order_by_trampoline::<T, F>
+    }
 }
 
 impl<T: Component> OrderByFunc<T> for extern "C" fn(Entity, &T, Entity, &T) -> i32 {
-    fn to_extern(self) -> extern "C" fn(Entity, &T, Entity, &T) -> i32 {
-        self
+    fn to_extern(self) -> ExternOrderByFunc {
+        // SAFETY:
+        // Same ABI, flecs passes valid pointers to components of type T as references.
+        unsafe { core::mem::transmute::<Self, ExternOrderByFunc>(self) }
     }
 }
+
+unsafe extern "C" fn order_by_trampoline<T, F>(
+    e1: Entity,
+    ptr1: *const c_void,
+    e2: Entity,
+    ptr2: *const c_void,
+) -> i32
+where
+    T: Component,
+    F: Fn(Entity, &T, Entity, &T) -> i32 + Copy + 'static,
+{
+    catch_orphan_panic(0, || {
+        // SAFETY:
+        // F is zero sized and Copy, so any instance of it is the same function.
+        let func = unsafe { NonNull::<F>::dangling().as_ptr().read() };
+        let (comp1, comp2) = unsafe { (&*(ptr1 as *const T), &*(ptr2 as *const T)) };
+        func(e1, comp1, e2, comp2)
+    })
+}
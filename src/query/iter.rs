@@ -13,7 +13,7 @@ use crate::{
         id::{IdFetcher, id},
     },
     entity::Entity,
-    world::{ComponentMap, World},
+    world::{ComponentMap, World, resume_orphan_panic},
 };
 
 /// Field of an component inside an iterator.
@@ -24,12 +24,36 @@ pub struct Field<'a, T: Component> {
     __m: PhantomData<&'a ()>,
 }
 
+impl<'a, T: Component> Field<'a, T> {
+    /// Number of entities the field covers.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    /// Is the field empty?
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// Pointer to the component of an entity, without a bounds check.
+    #[inline]
+    pub(crate) fn row_ptr(&self, index: usize) -> *mut T {
+        if self.is_on_self {
+            unsafe { self.cache_field.as_ptr().add(index) }
+        } else {
+            self.cache_field.as_ptr()
+        }
+    }
+}
+
 impl<'a, T: Component> Index<usize> for Field<'a, T> {
     type Output = T;
 
     fn index(&self, index: usize) -> &Self::Output {
         //check bounds
-        if index > self.length {
+        if index >= self.length {
             panic!(
                 "index out of bounds: the length is {}, but the index is {}",
                 self.length, index
@@ -47,7 +71,7 @@ impl<'a, T: Component> Index<usize> for Field<'a, T> {
 impl<'a, T: Component> IndexMut<usize> for Field<'a, T> {
     fn index_mut(&mut self, index: usize) -> &mut Self::Output {
         //check bounds
-        if index > self.length {
+        if index >= self.length {
             panic!(
                 "index out of bounds: the length is {}, but the index is {}",
                 self.length, index
@@ -62,6 +86,44 @@ impl<'a, T: Component> IndexMut<usize> for Field<'a, T> {
     }
 }
 
+/// Read-only field of a component inside an iterator, for terms with the \[in\] access
+/// modifier.
+pub struct ReadField<'a, T: Component>(Field<'a, T>);
+
+impl<'a, T: Component> ReadField<'a, T> {
+    /// Number of entities the field covers.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Is the field empty?
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Pointer to the component of an entity, without a bounds check.
+    #[inline]
+    pub(crate) fn row_ptr(&self, index: usize) -> *const T {
+        self.0.row_ptr(index)
+    }
+}
+
+impl<'a, T: Component> From<Field<'a, T>> for ReadField<'a, T> {
+    fn from(field: Field<'a, T>) -> Self {
+        Self(field)
+    }
+}
+
+impl<'a, T: Component> Index<usize> for ReadField<'a, T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.0[index]
+    }
+}
+
 /// Owned or pointer to sys iterator.
 #[allow(clippy::large_enum_variant)]
 pub(crate) enum MaybeOwnedIter {
@@ -103,16 +165,22 @@ impl Deref for MaybeOwnedIter {
 /// SYSTEM = false, means the iterator comes from a query.
 pub struct Iter<const SYSTEM: bool> {
     pub(crate) iter: MaybeOwnedIter,
+    /// Does the iterator come from a cached query?
+    pub(crate) cached: bool,
 }
 
 impl<const SYSTEM: bool> Iter<SYSTEM> {
     /// Jumps to the next table in the iterator.
+    ///
+    /// Resumes panics of order_by callbacks.
     #[inline]
     pub fn advance(&mut self) -> bool {
         if SYSTEM {
             unsafe { ecs_iter_next(self.iter.as_ptr()) }
         } else {
-            unsafe { ecs_query_next(self.iter.as_ptr()) }
+            let has_next = unsafe { ecs_query_next(self.iter.as_ptr()) };
+            resume_orphan_panic();
+            has_next
         }
     }
 
@@ -177,6 +245,26 @@ impl<const SYSTEM: bool> Iter<SYSTEM> {
         }
     }
 
+    /// Checks whether data the query reads changed in the current table, since the last
+    /// iteration of the query.
+    ///
+    /// Only works for cached queries, see [crate::query::Query::changed].
+    ///
+    /// # Panics
+    ///
+    /// If the query is not cached, e.g. for observers.
+    pub fn changed(&mut self) -> bool {
+        assert!(self.cached, "change detection requires a cached query");
+        unsafe { ecs_iter_changed(self.iter.as_ptr()) }
+    }
+
+    /// Skips the current table, so that the data the query writes is not marked as changed.
+    ///
+    /// Must be called before the fields of the table are accessed.
+    pub fn skip(&mut self) {
+        unsafe { ecs_iter_skip(self.iter.as_ptr()) };
+    }
+
     /// Get count of entities in the current table.
     #[inline]
     pub fn count(&self) -> usize {
@@ -189,6 +277,26 @@ impl<const SYSTEM: bool> Iter<SYSTEM> {
         self.iter.delta_time
     }
 
+    /// Gets the event the iterator was invoked for.
+    ///
+    /// Only relevant for observers.
+    #[inline]
+    pub fn event(&self) -> Entity {
+        self.iter.event
+    }
+
+    /// Gets the payload of a custom event.
+    ///
+    /// Returns None if the iterator was not invoked for event `E` or it carries no payload.
+    pub fn event_param<E: Component>(&self) -> Option<&E> {
+        //check event
+        let world_ref = self.world();
+        if self.iter.event != id::<E>().retrieve_id(&world_ref) {
+            return None;
+        }
+        unsafe { (self.iter.param as *const E).as_ref() }
+    }
+
     /// Get entity from the index.
     pub fn entity(&self, index: usize) -> Option<Entity> {
         //check bounds
@@ -0,0 +1,119 @@
+use flecs_ecs_sys::*;
+
+use crate::{
+    c_types::{ECS_CASCADE, ECS_CHILD_OF, ECS_UP, InOutKind, OperKind},
+    component::id::IdFetcher,
+    world::World,
+};
+
+/// Builder of programmatic query terms.
+///
+/// Implemented by query, system and observer builders.
+///
+/// Every term modifier (`inout`, `oper`, `src`, ...) applies to the last term added with
+/// `with`, `without` or `optional`.
+///
+/// # Note
+///
+/// Terms can be combined with an expression, however Flecs places the expression terms **after**
+/// the programmatic ones. The first programmatic term therefore always has the field index 0.
+pub trait TermBuilder: Sized {
+    /// World the ids are resolved in.
+    #[doc(hidden)]
+    fn term_world(&self) -> &World;
+
+    /// Query descriptor the terms are written to.
+    #[doc(hidden)]
+    fn query_desc(&mut self) -> &mut ecs_query_desc_t;
+
+    /// Number of programmatic terms added so far.
+    #[doc(hidden)]
+    fn term_count(&mut self) -> &mut usize;
+
+    /// Adds a new term matching the id.
+    fn with(mut self, id: impl IdFetcher) -> Self {
+        let id = id.retrieve_id(self.term_world());
+        let index = *self.term_count();
+        let terms = &mut self.query_desc().terms;
+        assert!(
+            index < terms.len(),
+            "a query can have at most {} terms",
+            terms.len()
+        );
+        terms[index] = ecs_term_t {
+            id,
+            ..Default::default()
+        };
+        *self.term_count() += 1;
+        self
+    }
+
+    /// Adds a new term which must not be matched.
+    #[inline]
+    fn without(self, id: impl IdFetcher) -> Self {
+        self.with(id).oper(OperKind::Not)
+    }
+
+    /// Adds a new term which may or may not be matched.
+    ///
+    /// Use [crate::query::iter::Iter::has] to check whether it was.
+    #[inline]
+    fn optional(self, id: impl IdFetcher) -> Self {
+        self.with(id).oper(OperKind::Optional)
+    }
+
+    /// Chains the current term with the next one using the Or operator.
+    #[inline]
+    fn or(self) -> Self {
+        self.oper(OperKind::Or)
+    }
+
+    /// Sets access modifier of the current term.
+    fn inout(mut self, kind: InOutKind) -> Self {
+        current_term(&mut self).inout = kind.into();
+        self
+    }
+
+    /// Sets operator of the current term.
+    fn oper(mut self, kind: OperKind) -> Self {
+        current_term(&mut self).oper = kind.into();
+        self
+    }
+
+    /// Sets source of the current term.
+    ///
+    /// Setting the source to the component itself matches a singleton.
+    fn src(mut self, id: impl IdFetcher) -> Self {
+        let id = id.retrieve_id(self.term_world());
+        current_term(&mut self).src.id = id;
+        self
+    }
+
+    /// Matches the current term by traversing the relationship upwards.
+    fn up(mut self, relationship: impl IdFetcher) -> Self {
+        let relationship = relationship.retrieve_id(self.term_world());
+        let term = current_term(&mut self);
+        term.src.id |= ECS_UP;
+        term.trav = relationship;
+        self
+    }
+
+    /// Matches the current term upwards and iterates the results breadth-first.
+    ///
+    /// Traverses [crate::flecs::ChildOf], unless a relationship was set with `up`.
+    fn cascade(mut self) -> Self {
+        let term = current_term(&mut self);
+        term.src.id |= ECS_UP | ECS_CASCADE;
+        if term.trav == 0 {
+            term.trav = ECS_CHILD_OF;
+        }
+        self
+    }
+}
+
+/// Retrieves the last added term.
+fn current_term<B: TermBuilder>(builder: &mut B) -> &mut ecs_term_t {
+    let index = *builder.term_count();
+    assert!(index > 0, "no term to modify, add one with `with` first");
+    &mut builder.query_desc().terms[index - 1]
+}
@@ -0,0 +1,335 @@
+use std::{ffi::CStr, marker::PhantomData};
+
+use flecs_ecs_sys::*;
+
+use crate::{
+    c_types::{InOutKind, OperKind, QueryCacheKind},
+    component::{
+        Component,
+        id::{IdFetcher, id},
+    },
+    entity::Entity,
+    world::World,
+};
+
+use super::{
+    Query, QueryBuilder,
+    iter::{Field, Iter, ReadField},
+    term::TermBuilder,
+};
+
+/// Single element of a typed query signature.
+///
+/// Implemented for `&T`, `&mut T`, `Option<&T>` and `Option<&mut T>`, where `T` is a data
+/// component. Tags have no data and must be added with [TermBuilder::with] instead.
+pub trait QueryTerm {
+    /// Component the term accesses.
+    type Comp: Component;
+    /// Access modifier of the term.
+    const INOUT: InOutKind;
+    /// Operator of the term.
+    const OPER: OperKind;
+    /// Whether the term writes to the component.
+    const MUTABLE: bool;
+    /// Table wide access to the term.
+    type Field<'a>;
+    /// Entity wide access to the term.
+    type Item<'a>;
+
+    /// Retrieves the field of the current table.
+    ///
+    /// # Safety
+    ///
+    /// The term at `index` must have been created from this type.
+    #[doc(hidden)]
+    unsafe fn field<'a, const SYSTEM: bool>(iter: &'a Iter<SYSTEM>, index: i8) -> Self::Field<'a>;
+
+    /// Retrieves the component of an entity from the field.
+    ///
+    /// # Safety
+    ///
+    /// The row must be in bounds and no other item of the same row may be alive.
+    #[doc(hidden)]
+    unsafe fn item<'a>(field: &Self::Field<'a>, row: usize) -> Self::Item<'a>;
+}
+
+impl<'r, T: Component> QueryTerm for &'r T {
+    type Comp = T;
+    const INOUT: InOutKind = InOutKind::In;
+    const OPER: OperKind = OperKind::And;
+    const MUTABLE: bool = false;
+    type Field<'a> = ReadField<'a, T>;
+    type Item<'a> = &'a T;
+
+    unsafe fn field<'a, const SYSTEM: bool>(iter: &'a Iter<SYSTEM>, index: i8) -> Self::Field<'a> {
+        unsafe { iter.get::<T>(index) }
+            .expect("required term is not set")
+            .into()
+    }
+
+    unsafe fn item<'a>(field: &Self::Field<'a>, row: usize) -> Self::Item<'a> {
+        unsafe { &*field.row_ptr(row) }
+    }
+}
+
+impl<'r, T: Component> QueryTerm for &'r mut T {
+    type Comp = T;
+    const INOUT: InOutKind = InOutKind::InOut;
+    const OPER: OperKind = OperKind::And;
+    const MUTABLE: bool = true;
+    type Field<'a> = Field<'a, T>;
+    type Item<'a> = &'a mut T;
+
+    unsafe fn field<'a, const SYSTEM: bool>(iter: &'a Iter<SYSTEM>, index: i8) -> Self::Field<'a> {
+        unsafe { iter.get::<T>(index) }.expect("required term is not set")
+    }
+
+    unsafe fn item<'a>(field: &Self::Field<'a>, row: usize) -> Self::Item<'a> {
+        unsafe { &mut *field.row_ptr(row) }
+    }
+}
+
+impl<'r, T: Component> QueryTerm for Option<&'r T> {
+    type Comp = T;
+    const INOUT: InOutKind = InOutKind::In;
+    const OPER: OperKind = OperKind::Optional;
+    const MUTABLE: bool = false;
+    type Field<'a> = Option<ReadField<'a, T>>;
+    type Item<'a> = Option<&'a T>;
+
+    unsafe fn field<'a, const SYSTEM: bool>(iter: &'a Iter<SYSTEM>, index: i8) -> Self::Field<'a> {
+        unsafe { iter.get::<T>(index) }.map(ReadField::from)
+    }
+
+    unsafe fn item<'a>(field: &Self::Field<'a>, row: usize) -> Self::Item<'a> {
+        field.as_ref().map(|field| unsafe { &*field.row_ptr(row) })
+    }
+}
+
+impl<'r, T: Component> QueryTerm for Option<&'r mut T> {
+    type Comp = T;
+    const INOUT: InOutKind = InOutKind::InOut;
+    const OPER: OperKind = OperKind::Optional;
+    const MUTABLE: bool = true;
+    type Field<'a> = Option<Field<'a, T>>;
+    type Item<'a> = Option<&'a mut T>;
+
+    unsafe fn field<'a, const SYSTEM: bool>(iter: &'a Iter<SYSTEM>, index: i8) -> Self::Field<'a> {
+        unsafe { iter.get::<T>(index) }
+    }
+
+    unsafe fn item<'a>(field: &Self::Field<'a>, row: usize) -> Self::Item<'a> {
+        field
+            .as_ref()
+            .map(|field| unsafe { &mut *field.row_ptr(row) })
+    }
+}
+
+/// Signature of a typed query, a tuple of [QueryTerm]s.
+///
+/// For example `(&Position, &mut Velocity, Option<&Mass>)`.
+pub trait QueryData {
+    /// Tuple of table wide accesses.
+    type Fields<'a>;
+    /// Tuple of entity wide accesses.
+    type Item<'a>;
+
+    /// Adds the terms of the signature to a builder.
+    #[doc(hidden)]
+    fn populate<B: TermBuilder>(builder: B) -> B;
+
+    /// Retrieves the fields of the current table.
+    ///
+    /// # Safety
+    ///
+    /// The first terms of the iterator must have been created by [QueryData::populate].
+    #[doc(hidden)]
+    unsafe fn fields<'a, const SYSTEM: bool>(iter: &'a Iter<SYSTEM>) -> Self::Fields<'a>;
+
+    /// Retrieves the components of an entity.
+    ///
+    /// # Safety
+    ///
+    /// The row must be in bounds and no other item of the same row may be alive.
+    #[doc(hidden)]
+    unsafe fn item<'a>(fields: &Self::Fields<'a>, row: usize) -> Self::Item<'a>;
+}
+
+/// Panics if a component is accessed mutably by more than one term.
+fn check_aliasing(ids: &[(Entity, bool)]) {
+    for (index, (id, mutable)) in ids.iter().enumerate() {
+        for (other_id, other_mutable) in &ids[index + 1..] {
+            if id == other_id && (*mutable || *other_mutable) {
+                panic!("typed query accesses a component mutably more than once");
+            }
+        }
+    }
+}
+
+macro_rules! impl_query_data {
+    ($($name:ident : $index:tt),+) => {
+        impl<$($name: QueryTerm),+> QueryData for ($($name,)+) {
+            type Fields<'a> = ($($name::Field<'a>,)+);
+            type Item<'a> = ($($name::Item<'a>,)+);
+
+            fn populate<B: TermBuilder>(builder: B) -> B {
+                let ids = [$(
+                    (id::<$name::Comp>().retrieve_id(builder.term_world()), $name::MUTABLE)
+                ),+];
+                check_aliasing(&ids);
+                $(
+                    let builder = builder
+                        .with(id::<$name::Comp>())
+                        .inout($name::INOUT)
+                        .oper($name::OPER);
+                )+
+                builder
+            }
+
+            unsafe fn fields<'a, const SYSTEM: bool>(iter: &'a Iter<SYSTEM>) -> Self::Fields<'a> {
+                unsafe { ($($name::field(iter, $index),)+) }
+            }
+
+            unsafe fn item<'a>(fields: &Self::Fields<'a>, row: usize) -> Self::Item<'a> {
+                unsafe { ($($name::item(&fields.$index, row),)+) }
+            }
+        }
+    };
+}
+
+impl_query_data!(A: 0);
+impl_query_data!(A: 0, B: 1);
+impl_query_data!(A: 0, B: 1, C: 2);
+impl_query_data!(A: 0, B: 1, C: 2, D: 3);
+impl_query_data!(A: 0, B: 1, C: 2, D: 3, E: 4);
+impl_query_data!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5);
+impl_query_data!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6);
+impl_query_data!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7);
+
+/// Builder for creating typed queries.
+///
+/// Terms of the signature come first, additional terms and expressions are placed after them.
+#[derive(Debug)]
+pub struct TypedQueryBuilder<'a, Q: QueryData> {
+    pub(crate) builder: QueryBuilder<'a>,
+    pub(crate) __m: PhantomData<fn() -> Q>,
+}
+
+impl<'a, Q: QueryData> TermBuilder for TypedQueryBuilder<'a, Q> {
+    fn term_world(&self) -> &World {
+        self.builder.world
+    }
+
+    fn query_desc(&mut self) -> &mut ecs_query_desc_t {
+        &mut self.builder.inner
+    }
+
+    fn term_count(&mut self) -> &mut usize {
+        &mut self.builder.term_count
+    }
+}
+
+impl<'a, Q: QueryData> TypedQueryBuilder<'a, Q> {
+    /// Sets an expression for additional terms.
+    ///
+    /// This allocates a string, due to lifetimes.
+    pub fn expression(mut self, expr: &CStr) -> Self {
+        self.builder = self.builder.expression(expr);
+        self
+    }
+
+    /// Sets query's cache kind.
+    pub fn set_cache(mut self, kind: QueryCacheKind) -> Self {
+        self.builder = self.builder.set_cache(kind);
+        self
+    }
+
+    /// Sets query to match prefabs.
+    pub fn match_prefabs(mut self) -> Self {
+        self.builder = self.builder.match_prefabs();
+        self
+    }
+
+    /// Sets query to match disabled.
+    pub fn match_disabled(mut self) -> Self {
+        self.builder = self.builder.match_disabled();
+        self
+    }
+
+    /// Builds the query.
+    pub fn build(self) -> TypedQuery<Q> {
+        TypedQuery {
+            query: self.builder.build(),
+            __m: PhantomData,
+        }
+    }
+
+    /// Builds the query to an associated named entity.
+    pub fn build_with_entity_named(self, name: &CStr) -> TypedQuery<Q> {
+        TypedQuery {
+            query: self.builder.build_with_entity_named(name),
+            __m: PhantomData,
+        }
+    }
+}
+
+/// Query with statically typed terms.
+///
+/// The query must not out live the world it was created in.
+pub struct TypedQuery<Q: QueryData> {
+    query: Query,
+    __m: PhantomData<fn() -> Q>,
+}
+
+impl<Q: QueryData> TypedQuery<Q> {
+    /// Accesses the untyped query.
+    #[inline]
+    pub fn query(&self) -> &Query {
+        &self.query
+    }
+
+    /// Begin untyped query iteration.
+    #[inline]
+    pub fn iter(&self) -> Iter<false> {
+        self.query.iter()
+    }
+
+    /// Calls the callback for every matched entity.
+    pub fn each<F>(&self, mut callback: F)
+    where
+        F: FnMut(Q::Item<'_>),
+    {
+        self.each_entity(|_, item| callback(item));
+    }
+
+    /// Calls the callback for every matched entity, together with the entity id.
+    pub fn each_entity<F>(&self, mut callback: F)
+    where
+        F: FnMut(Entity, Q::Item<'_>),
+    {
+        let mut iter = self.query.iter();
+        while iter.advance() {
+            // SAFETY:
+            // The first terms were created by the signature and each row is visited once.
+            let fields = unsafe { Q::fields(&iter) };
+            for row in 0..iter.count() {
+                let entity = iter.entity(row).unwrap();
+                callback(entity, unsafe { Q::item(&fields, row) });
+            }
+        }
+    }
+
+    /// Calls the callback for every matched table with its fields.
+    pub fn run<F>(&self, mut callback: F)
+    where
+        F: FnMut(&Iter<false>, Q::Fields<'_>),
+    {
+        let mut iter = self.query.iter();
+        while iter.advance() {
+            // SAFETY:
+            // The first terms were created by the signature.
+            let fields = unsafe { Q::fields(&iter) };
+            callback(&iter, fields);
+        }
+    }
+}
@@ -1,31 +1,39 @@
 use std::{
     ffi::{CStr, CString, c_void},
-    ptr::NonNull,
+    ptr::{NonNull, null_mut},
 };
 
 use flecs_ecs_sys::*;
 
 use crate::{
-    c_types::{ECS_QUERY_MATCH_DISABLED, ECS_QUERY_MATCH_EMPTY_TABLES, ECS_QUERY_MATCH_PREFAB},
+    c_types::{
+        ECS_DISABLED, ECS_QUERY_MATCH_DISABLED, ECS_QUERY_MATCH_EMPTY_TABLES,
+        ECS_QUERY_MATCH_PREFAB,
+    },
     component::{
         Component,
         id::{IdFetcher, id},
     },
     entity::Entity,
-    flecs::{DependsOn, pipeline::OnUpdate},
+    flecs::{DependsOn, Wildcard, pipeline::OnUpdate, system::TickSource, timer::RateFilter},
     query::{
+        Query, QueryCacheKind,
         callbacks::OrderByFunc,
         iter::{Iter, MaybeOwnedIter},
+        term::TermBuilder,
     },
-    world::{ComponentMap, World},
+    world::{ComponentMap, World, assert_unborrowed, catch_panic, is_panicking},
 };
 
 /// Binding context for systems.
+///
+/// Owns the callback together with its state, both are dropped when the system is deleted.
 #[repr(C)]
-pub struct CallbackContext<F: Fn(Iter<true>)> {
+pub struct CallbackContext<S, F: FnMut(&mut S, Iter<true>)> {
     /// This field must always be first!
-    component_map: *mut ComponentMap,
-    func: F,
+    pub(crate) component_map: *mut ComponentMap,
+    pub(crate) state: S,
+    pub(crate) func: F,
 }
 
 /// Builder for creating queries. Allows you to set certain flags and the components to request.
@@ -34,9 +42,24 @@ pub struct SystemBuilder<'a> {
     pub(crate) inner: ecs_system_desc_t,
     pub(crate) expr: Option<CString>,
     pub(crate) kind: Entity,
+    pub(crate) term_count: usize,
     pub(crate) world: &'a World,
 }
 
+impl<'a> TermBuilder for SystemBuilder<'a> {
+    fn term_world(&self) -> &World {
+        self.world
+    }
+
+    fn query_desc(&mut self) -> &mut ecs_query_desc_t {
+        &mut self.inner.query
+    }
+
+    fn term_count(&mut self) -> &mut usize {
+        &mut self.term_count
+    }
+}
+
 impl<'a> SystemBuilder<'a> {
     /// Sets an expression as the base for the query.
     ///
@@ -68,15 +91,7 @@ impl<'a> SystemBuilder<'a> {
     /// Orders output by a component.
     pub fn order_by<T: Component, F: OrderByFunc<T>>(mut self, callback: F) -> Self {
         self.inner.query.order_by = id::<T>().retrieve_id(self.world);
-        self.inner.query.order_by_callback = unsafe {
-            // SAFETY:
-            // Components are sized, so references are thin, though it is an evil hack.
-            let extern_fun = std::mem::transmute::<
-                extern "C" fn(u64, &T, u64, &T) -> i32,
-                unsafe extern "C" fn(u64, *const c_void, u64, *const c_void) -> i32,
-            >(callback.to_extern());
-            Some(extern_fun)
-        };
+        self.inner.query.order_by_callback = Some(callback.to_extern());
         self
     }
 
@@ -102,43 +117,54 @@ impl<'a> SystemBuilder<'a> {
     }
 
     /// Finishes this system with default callback.
-    pub fn build<F>(mut self, callback: F)
+    pub fn build<F>(self, mut callback: F) -> System
     where
-        F: Fn(Iter<true>) + 'static,
+        F: FnMut(Iter<true>) + 'static,
+    {
+        self.build_with_state((), move |_, iter| callback(iter))
+    }
+
+    /// Finishes this system with default callback.
+    pub fn build_named<F>(self, name: &CStr, mut callback: F) -> System
+    where
+        F: FnMut(Iter<true>) + 'static,
+    {
+        self.build_named_with_state(name, (), move |_, iter| callback(iter))
+    }
+
+    /// Finishes this system with a callback owning system-local state.
+    ///
+    /// The state is passed to every invocation of the callback and dropped together with the
+    /// system.
+    ///
+    /// # Multithreading
+    ///
+    /// Systems created by this builder are not multithreaded, flecs never invokes them from
+    /// more than one thread at a time, so neither the state nor the callback need to be `Send`
+    /// or `Sync`. The world, and with it the state, must still stay on the thread it was
+    /// created on.
+    pub fn build_with_state<S, F>(self, state: S, callback: F) -> System
+    where
+        S: 'static,
+        F: FnMut(&mut S, Iter<true>) + 'static,
     {
-        //set callback
-        self.inner.callback = Some(system_callback::<F>);
-        self.inner.callback_ctx = Box::leak(Box::new(CallbackContext {
-            component_map: self.world.component_map.as_ptr(),
-            func: callback,
-        })) as *mut _ as *mut c_void;
-        self.inner.callback_ctx_free = Some(callback_ctx_free::<F>);
         //creates an entity
         let entity = self.world.entity();
         //adds a kind if any
         if self.kind != 0 {
             entity.add((DependsOn, self.kind));
         }
-        //sets the entity
-        self.inner.entity = entity.id();
-        //creates the system
-        unsafe {
-            ecs_system_init(self.world.ptr(), &self.inner as *const _);
-        }
+        self.finish(entity.id(), state, callback)
     }
 
-    /// Finishes this system with default callback.
-    pub fn build_named<F>(mut self, name: &CStr, callback: F)
+    /// Finishes this named system with a callback owning system-local state.
+    ///
+    /// See [Self::build_with_state].
+    pub fn build_named_with_state<S, F>(self, name: &CStr, state: S, callback: F) -> System
     where
-        F: Fn(Iter<true>) + 'static,
+        S: 'static,
+        F: FnMut(&mut S, Iter<true>) + 'static,
     {
-        //set callback
-        self.inner.callback = Some(system_callback::<F>);
-        self.inner.callback_ctx = Box::leak(Box::new(CallbackContext {
-            component_map: self.world.component_map.as_ptr(),
-            func: callback,
-        })) as *mut _ as *mut c_void;
-        self.inner.callback_ctx_free = Some(callback_ctx_free::<F>);
         //creates an entity
         let entity = self.world.entity_named(name);
         //adds a kind if any
@@ -147,29 +173,229 @@ impl<'a> SystemBuilder<'a> {
         } else {
             entity.add((DependsOn, OnUpdate));
         }
+        self.finish(entity.id(), state, callback)
+    }
+
+    /// Creates the system on an entity.
+    fn finish<S, F>(mut self, entity: Entity, state: S, callback: F) -> System
+    where
+        S: 'static,
+        F: FnMut(&mut S, Iter<true>) + 'static,
+    {
+        //set callback
+        self.inner.callback = Some(system_callback::<S, F>);
+        self.inner.callback_ctx = Box::leak(Box::new(CallbackContext {
+            component_map: self.world.component_map.as_ptr(),
+            state,
+            func: callback,
+        })) as *mut _ as *mut c_void;
+        self.inner.callback_ctx_free = Some(callback_ctx_free::<S, F>);
         //sets the entity
-        self.inner.entity = entity.id();
+        self.inner.entity = entity;
         //creates the system
+        let entity_id = unsafe { ecs_system_init(self.world.ptr(), &self.inner as *const _) };
+        assert!(entity_id != 0, "failed to create a system");
+        System {
+            world_ptr: self.world.ptr,
+            component_map: self.world.component_map,
+            entity_id,
+        }
+    }
+}
+
+/// Handle to a system created in an ECS world.
+///
+/// The handle does not own the system, dropping it keeps the system alive.
+/// It must not outlive the world it was created in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct System {
+    world_ptr: NonNull<ecs_world_t>,
+    component_map: NonNull<ComponentMap>,
+    entity_id: Entity,
+}
+
+impl System {
+    /// Gets system's entity id.
+    #[inline]
+    pub fn id(&self) -> Entity {
+        self.entity_id
+    }
+
+    /// Accesses the world the system lives in.
+    ///
+    /// Creates a non owning reference, dropping it does not drop neither the world nor the
+    /// component map.
+    #[inline]
+    pub fn world(&self) -> World {
+        unsafe { World::from_ptr_and_map(self.world_ptr.as_ptr(), self.component_map.as_ptr()) }
+    }
+
+    /// Runs the system outside of the pipeline.
+    ///
+    /// Panics of the callback are resumed once the system finishes.
+    ///
+    /// # Panics
+    ///
+    /// If a component is borrowed by a guard, see [crate::entity::EntityView::get_ref].
+    pub fn run(&self, delta_time: f32) {
+        unsafe { assert_unborrowed(self.world_ptr.as_ptr(), "run a system") };
         unsafe {
-            ecs_system_init(self.world.ptr(), &self.inner as *const _);
+            ecs_run(
+                self.world_ptr.as_ptr(),
+                self.entity_id,
+                delta_time,
+                null_mut(),
+            )
+        };
+        self.world().resume_panic();
+    }
+
+    /// Runs the system on a part of its matched entities.
+    ///
+    /// Entities are split into `stage_count` parts, `stage_current` selects which one to run.
+    pub fn run_worker(&self, stage_current: i32, stage_count: i32, delta_time: f32) {
+        unsafe { assert_unborrowed(self.world_ptr.as_ptr(), "run a system") };
+        unsafe {
+            ecs_run_worker(
+                self.world_ptr.as_ptr(),
+                self.entity_id,
+                stage_current,
+                stage_count,
+                delta_time,
+                null_mut(),
+            )
+        };
+        self.world().resume_panic();
+    }
+
+    /// Enables the system.
+    #[inline]
+    pub fn enable(&self) {
+        unsafe { ecs_enable(self.world_ptr.as_ptr(), self.entity_id, true) };
+    }
+
+    /// Disables the system.
+    #[inline]
+    pub fn disable(&self) {
+        unsafe { ecs_enable(self.world_ptr.as_ptr(), self.entity_id, false) };
+    }
+
+    /// Is the system enabled?
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        unsafe { !ecs_has_id(self.world_ptr.as_ptr(), self.entity_id, ECS_DISABLED) }
+    }
+
+    /// Moves the system to another phase of the pipeline.
+    pub fn set_kind(&self, id: impl IdFetcher) {
+        let world = self.world();
+        let entity = world.view(self.entity_id);
+        entity.remove((DependsOn, Wildcard));
+        entity.add((DependsOn, id.retrieve_id(&world)));
+    }
+
+    /// Sets system's interval.
+    ///
+    /// The system will run once per `interval` seconds.
+    pub fn set_interval(&self, interval: f32) {
+        unsafe { ecs_set_interval(self.world_ptr.as_ptr(), self.entity_id, interval) };
+    }
+
+    /// Gets system's interval, 0 if not set.
+    pub fn interval(&self) -> f32 {
+        unsafe { ecs_get_interval(self.world_ptr.as_ptr(), self.entity_id) }
+    }
+
+    /// Sets system's rate.
+    ///
+    /// If the rate is set to 2, the system will run every second frame.
+    /// If set to 3, it will run every third frame, and so on.
+    pub fn set_rate(&self, rate: u32) {
+        unsafe { ecs_set_rate(self.world_ptr.as_ptr(), self.entity_id, rate as i32, 0) };
+    }
+
+    /// Gets system's rate, if set.
+    pub fn rate(&self) -> Option<u32> {
+        let world = self.world();
+        let filter = unsafe { world.view(self.entity_id).get::<RateFilter>() }?;
+        Some(filter.rate as u32)
+    }
+
+    /// Sets a tick source of the system, such as a timer or another system.
+    pub fn set_tick_source(&self, tick_source: impl IdFetcher) {
+        let world = self.world();
+        let tick_source = tick_source.retrieve_id(&world);
+        unsafe { ecs_set_tick_source(self.world_ptr.as_ptr(), self.entity_id, tick_source) };
+    }
+
+    /// Did the system's tick source tick this frame?
+    ///
+    /// Systems without a tick source are always ticking.
+    pub fn is_ticking(&self) -> bool {
+        let world = self.world();
+        match unsafe { world.view(self.entity_id).get::<TickSource>() } {
+            Some(tick_source) => tick_source.tick,
+            None => true,
+        }
+    }
+
+    /// Accesses the query of the system.
+    pub fn query(&self) -> Query {
+        let system = unsafe { ecs_system_get(self.world_ptr.as_ptr(), self.entity_id) };
+        assert!(!system.is_null(), "entity is not a system");
+        Query {
+            world_ptr: self.world_ptr,
+            component_map: self.component_map,
+            query: NonNull::new(unsafe { (*system).query }).unwrap(),
+            //the query is owned by the system
+            entity_id: Some(self.entity_id),
+            //queries of systems are cached unless stated otherwise
+            cache_kind: match unsafe { (*(*system).query).cache_kind }.into() {
+                QueryCacheKind::Default => QueryCacheKind::Auto,
+                kind => kind,
+            },
         }
     }
+
+    /// Deletes the system.
+    pub fn delete(self) {
+        unsafe { ecs_delete(self.world_ptr.as_ptr(), self.entity_id) };
+    }
 }
 
-unsafe extern "C" fn system_callback<F: Fn(Iter<true>) + 'static>(iter: *mut ecs_iter_t) {
+unsafe extern "C" fn system_callback<S, F>(iter: *mut ecs_iter_t)
+where
+    S: 'static,
+    F: FnMut(&mut S, Iter<true>) + 'static,
+{
+    //skip the rest of the frame after a panic
+    let world = unsafe { (*iter).world };
+    if unsafe { is_panicking(world) } {
+        return;
+    }
     //retrieve the function
-    let context = unsafe { (*iter).callback_ctx as *mut CallbackContext<F> };
-    let context = unsafe { context.as_ref().unwrap() };
+    let context = unsafe { (*iter).callback_ctx as *mut CallbackContext<S, F> };
+    // SAFETY:
+    // The system is not multithreaded, so the context is never accessed concurrently.
+    let context = unsafe { context.as_mut().unwrap() };
     //create iterator
+    //queries of systems are cached unless stated otherwise
+    let cached = unsafe { (*iter).query.as_ref() }
+        .is_some_and(|query| !QueryCacheKind::from(query.cache_kind).is_none());
     let iter = Iter::<true> {
         iter: MaybeOwnedIter::Ptr(NonNull::new(iter).unwrap()),
+        cached,
     };
-    //call callback
-    (context.func)(iter);
+    //call callback, panics must not unwind into flecs
+    unsafe { catch_panic(world, (), || (context.func)(&mut context.state, iter)) };
 }
 
-unsafe extern "C" fn callback_ctx_free<F: Fn(Iter<true>) + 'static>(ctx: *mut c_void) {
+unsafe extern "C" fn callback_ctx_free<S, F>(ctx: *mut c_void)
+where
+    S: 'static,
+    F: FnMut(&mut S, Iter<true>) + 'static,
+{
     // SAFETY:
-    // ctx is a pointer to CallbackContext<F>, so we can safely cast it back.
-    let _ = unsafe { Box::from_raw(ctx as *mut CallbackContext<F>) };
+    // ctx is a pointer to CallbackContext<S, F>, so we can safely cast it back.
+    let _ = unsafe { Box::from_raw(ctx as *mut CallbackContext<S, F>) };
 }
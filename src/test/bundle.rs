@@ -0,0 +1,110 @@
+use std::rc::Rc;
+
+use crate::{
+    component::{Component, id::id},
+    world::World,
+};
+
+struct Position {
+    x: f32,
+    y: f32,
+}
+
+impl Component for Position {}
+
+struct Velocity {
+    x: f32,
+}
+
+impl Component for Velocity {}
+
+struct Particle;
+
+impl Component for Particle {}
+
+/// Keeps a reference count, to check the data is owned exactly once.
+struct Tracked(Rc<usize>);
+
+impl Component for Tracked {}
+
+/// Clonable and tracked, flecs has a copy hook for it.
+#[derive(Clone, Default)]
+struct Named {
+    name: String,
+    counter: Rc<usize>,
+}
+
+impl Component for Named {}
+
+#[test]
+fn spawn_batch_test() {
+    let mut world = World::new();
+    world.component::<Position>(c"Position");
+    world.component::<Velocity>(c"Velocity");
+    world.component::<Particle>(c"Particle");
+    world.component::<Tracked>(c"Tracked");
+
+    //spawn entities with data and tags
+    let entities = world.spawn_batch(100, |i| {
+        (
+            Position {
+                x: i as f32,
+                y: -(i as f32),
+            },
+            Velocity { x: 1.0 },
+            Particle,
+        )
+    });
+    assert_eq!(entities.len(), 100);
+    for (i, entity) in entities.iter().enumerate() {
+        let entity = world.view(*entity);
+        assert!(entity.has(id::<Particle>()));
+        let position = unsafe { entity.get::<Position>() }.unwrap();
+        assert_eq!((position.x, position.y), (i as f32, -(i as f32)));
+        assert_eq!(unsafe { entity.get::<Velocity>() }.unwrap().x, 1.0);
+    }
+    assert!(world.spawn_batch(0, |_| (Particle,)).is_empty());
+
+    //components with drop are owned by the world
+    let counter = Rc::new(7);
+    let tracked = world.spawn_batch(10, |_| (Tracked(counter.clone()),));
+    assert_eq!(Rc::strong_count(&counter), 11);
+    assert_eq!(
+        *unsafe { world.view(tracked[3]).get::<Tracked>() }
+            .unwrap()
+            .0,
+        7
+    );
+    world.view(tracked[0]).delete();
+    assert_eq!(Rc::strong_count(&counter), 10);
+    drop(world);
+    assert_eq!(Rc::strong_count(&counter), 1);
+}
+
+#[test]
+#[should_panic(expected = "contains a component twice")]
+fn spawn_batch_duplicate_test() {
+    let mut world = World::new();
+    world.component::<Velocity>(c"Velocity");
+    world.spawn_batch(2, |_| (Velocity { x: 1.0 }, Velocity { x: 2.0 }));
+}
+
+#[test]
+fn spawn_batch_clone_test() {
+    let mut world = World::new();
+    world.component_clone::<Named>(c"Named");
+
+    //moved into the world, even though the component can be copied
+    let counter = Rc::new(7);
+    let named = world.spawn_batch(10, |i| {
+        (Named {
+            name: format!("named {i}"),
+            counter: counter.clone(),
+        },)
+    });
+    assert_eq!(Rc::strong_count(&counter), 11);
+    let third = unsafe { world.view(named[3]).get::<Named>() }.unwrap();
+    assert_eq!((third.name.as_str(), *third.counter), ("named 3", 7));
+    drop(world);
+    assert_eq!(Rc::strong_count(&counter), 1);
+}
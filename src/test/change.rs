@@ -0,0 +1,119 @@
+use crate::{
+    component::{Component, id::id},
+    entity::Entity,
+    query::{InOutKind, Query, QueryCacheKind, term::TermBuilder},
+    world::World,
+};
+
+struct Position {
+    x: f32,
+}
+
+impl Component for Position {}
+
+struct Velocity {
+    _x: f32,
+}
+
+impl Component for Velocity {}
+
+/// Iterates a query, returns entities of tables which changed.
+fn changed_entities(query: &Query) -> Vec<Entity> {
+    let mut changed = Vec::new();
+    let mut iter = query.iter();
+    while iter.advance() {
+        if iter.changed() {
+            changed.extend((0..iter.count()).filter_map(|i| iter.entity(i)));
+        }
+    }
+    changed
+}
+
+#[test]
+fn change_test() {
+    let mut world = World::new();
+    world.component::<Position>(c"Position");
+    world.component::<Velocity>(c"Velocity");
+    let alice = world.entity();
+    alice.set_comp(Position { x: 1.0 });
+    let mut bob = world.entity();
+    bob.set_comp(Position { x: 2.0 });
+    bob.set_comp(Velocity { _x: 1.0 });
+    let reader = world
+        .query()
+        .with(id::<Position>())
+        .inout(InOutKind::In)
+        .set_cache(QueryCacheKind::Auto)
+        .build();
+
+    //everything is new at first
+    assert!(reader.changed());
+    assert_eq!(changed_entities(&reader).len(), 2);
+    assert!(!reader.changed());
+
+    //set marks the table of the entity
+    alice.set_comp(Position { x: 3.0 });
+    assert!(reader.changed());
+    assert_eq!(changed_entities(&reader), [alice.id()]);
+    assert!(!reader.changed());
+
+    //so do explicit notifications
+    unsafe { bob.get_mut::<Position>() }.unwrap().x = 4.0;
+    assert!(!reader.changed());
+    bob.modified(id::<Position>());
+    assert!(reader.changed());
+    assert_eq!(changed_entities(&reader), [bob.id()]);
+
+    //writing queries mark tables unless they are skipped
+    let writer = world
+        .query()
+        .with(id::<Position>())
+        .inout(InOutKind::InOut)
+        .set_cache(QueryCacheKind::Auto)
+        .build();
+    let mut iter = writer.iter();
+    while iter.advance() {
+        iter.skip();
+    }
+    assert!(!reader.changed());
+    let mut iter = writer.iter();
+    while iter.advance() {
+        if iter.entity(0) == Some(alice.id()) {
+            unsafe { iter.get::<Position>(0) }.unwrap()[0].x = 5.0;
+        } else {
+            iter.skip();
+        }
+    }
+    assert!(reader.changed());
+    assert_eq!(changed_entities(&reader), [alice.id()]);
+}
+
+#[test]
+#[should_panic]
+fn change_uncached_test() {
+    let mut world = World::new();
+    world.component::<Position>(c"Position");
+    let query = world
+        .query()
+        .with(id::<Position>())
+        .set_cache(QueryCacheKind::None)
+        .build();
+    query.changed();
+}
+
+#[test]
+#[should_panic(expected = "requires a cached query")]
+fn change_uncached_iter_test() {
+    let mut world = World::new();
+    world.component::<Position>(c"Position");
+    world.entity().set_comp(Position { x: 0.0 });
+    let query = world
+        .query()
+        .with(id::<Position>())
+        .set_cache(QueryCacheKind::None)
+        .build();
+    let mut iter = query.iter();
+    while iter.advance() {
+        iter.changed();
+    }
+}
@@ -1,4 +1,8 @@
-use crate::{flecs::ChildOf, world::World};
+use crate::{
+    entity::{EntityView, hierarchy::Traversal},
+    flecs::ChildOf,
+    world::World,
+};
 
 #[test]
 fn child_test() {
@@ -28,3 +32,57 @@ fn child_test() {
     assert_eq!(cecil.path(), "alice.cecil");
     assert_eq!(david.path(), "alice.cecil.david");
 }
+
+#[test]
+fn child_traversal_test() {
+    let world = World::new();
+
+    //create hierarchy alice -> (bob, cecil -> david)
+    let alice = world.entity_named(c"alice");
+    let (bob, cecil) = world.with_scope(alice, || {
+        (world.entity_named(c"bob"), world.entity_named(c"cecil"))
+    });
+    let david = world.entity_named(c"david");
+    david.child_of(cecil);
+    assert_eq!(world.lookup(c"alice.cecil.david").unwrap(), david);
+
+    //parents and depth
+    assert_eq!(alice.parent(), None);
+    assert_eq!(bob.parent(), Some(alice));
+    assert_eq!(david.parent(), Some(cecil));
+    assert_eq!(alice.depth(), 0);
+    assert_eq!(cecil.depth(), 1);
+    assert_eq!(david.depth(), 2);
+
+    //children
+    let mut children: Vec<EntityView> = alice.children().collect();
+    children.sort_by_key(|child| child.id());
+    let mut expected = vec![bob, cecil];
+    expected.sort_by_key(|child| child.id());
+    assert_eq!(children, expected);
+    assert_eq!(david.children().count(), 0);
+    //dropping an unfinished iterator
+    assert!(alice.children().next().is_some());
+
+    //descendants
+    let depth_first: Vec<EntityView> = alice.descendants(Traversal::DepthFirst).collect();
+    assert_eq!(depth_first.len(), 3);
+    let cecil_at = depth_first.iter().position(|e| *e == cecil).unwrap();
+    assert_eq!(depth_first[cecil_at + 1], david);
+    let breadth_first: Vec<EntityView> = alice.descendants(Traversal::BreadthFirst).collect();
+    assert_eq!(breadth_first.len(), 3);
+    assert_eq!(breadth_first[2], david);
+
+    //reparenting
+    david.child_of(bob);
+    assert_eq!(david.parent(), Some(bob));
+    assert_eq!(david.path(), "alice.bob.david");
+    assert_eq!(cecil.children().count(), 0);
+    david.remove_parent();
+    assert_eq!(david.parent(), None);
+    assert_eq!(david.depth(), 0);
+    assert_eq!(world.lookup(c"david").unwrap(), david);
+
+    //the scope is restored
+    assert_eq!(world.entity().parent(), None);
+}
@@ -0,0 +1,45 @@
+use crate::{
+    component::{Component, id::id},
+    world::World,
+};
+
+struct Position {
+    x: f32,
+    y: f32,
+}
+
+impl Component for Position {}
+
+struct Velocity {
+    x: f32,
+}
+
+impl Component for Velocity {}
+
+#[test]
+fn comp_ref_test() {
+    let mut world = World::new();
+    world.component::<Position>(c"Position");
+    world.component::<Velocity>(c"Velocity");
+
+    //references may be created before the component is set
+    let alice = world.entity();
+    let mut position = alice.comp_ref::<Position>();
+    assert_eq!(position.entity(), alice);
+    assert!(unsafe { position.get() }.is_none());
+    alice.set_comp(Position { x: 1.0, y: 2.0 });
+    let pos = unsafe { position.get() }.unwrap();
+    assert_eq!((pos.x, pos.y), (1.0, 2.0));
+
+    //writes are visible to the entity
+    unsafe { position.get_mut() }.unwrap().x = 5.0;
+    assert_eq!(unsafe { alice.get::<Position>() }.unwrap().x, 5.0);
+
+    //the reference follows the entity to other tables
+    alice.set_comp(Velocity { x: 3.0 });
+    assert_eq!(unsafe { position.get() }.unwrap().x, 5.0);
+    let velocity = alice.comp_ref::<Velocity>();
+    assert_eq!(unsafe { velocity.get() }.unwrap().x, 3.0);
+    alice.remove(id::<Position>());
+    assert!(unsafe { position.get() }.is_none());
+}
@@ -0,0 +1,136 @@
+use crate::{
+    bundle::Bundle,
+    component::{Component, id::id},
+    flecs::{CanToggle, Sparse},
+    world::World,
+};
+
+#[derive(Component, Clone, Default)]
+#[flecs(symbol = "game.Position", traits(Sparse, CanToggle), clone)]
+struct Position {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Component)]
+#[flecs(tag)]
+struct Player;
+
+#[derive(Component)]
+struct Velocity {
+    _x: f32,
+}
+
+#[derive(Component, Default)]
+#[flecs(default)]
+struct Score {
+    points: u32,
+}
+
+#[derive(Component)]
+struct Stats {
+    _hp: u32,
+}
+
+#[derive(Component)]
+struct StatsAlias {
+    _hp: core::primitive::u32,
+}
+
+#[derive(Component)]
+struct StatsRenamed {
+    _health: u32,
+}
+
+#[derive(Component)]
+struct StatsRetyped {
+    _hp: f32,
+}
+
+#[derive(Bundle)]
+struct PlayerBundle {
+    position: Position,
+    velocity: Velocity,
+    player: Player,
+}
+
+#[derive(Bundle)]
+struct Moving(Position, Velocity);
+
+#[test]
+fn derive_test() {
+    let mut world = World::new();
+    //register components from their types
+    let position = world.register::<Position>().id();
+    world.register::<Player>();
+    world.register::<Velocity>();
+    world.register::<Score>();
+    assert_eq!(
+        world.lookup_symbol(c"game.Position").unwrap().id(),
+        position
+    );
+    assert!(world.lookup_symbol(c"Velocity").is_some());
+
+    //traits are added
+    let component = world.view(position);
+    assert!(component.has(Sparse));
+    assert!(component.has(CanToggle));
+
+    //components work as usual
+    let alice = world.entity_named(c"alice");
+    alice.add(id::<Player>());
+    alice.set_comp(Position { x: 1.0, y: 2.0 });
+    let pos = unsafe { alice.get::<Position>() }.unwrap();
+    assert_eq!((pos.x, pos.y), (1.0, 2.0));
+    assert!(alice.has(id::<Player>()));
+
+    //components with a default constructor can be added without data
+    alice.add(id::<Score>());
+    assert_eq!(unsafe { alice.get::<Score>() }.unwrap().points, 0);
+}
+
+#[test]
+fn derive_bundle_test() {
+    let mut world = World::new();
+    world.register::<Position>();
+    world.register::<Player>();
+    world.register::<Velocity>();
+
+    //bundles with named fields
+    let bob = world.entity_with(PlayerBundle {
+        position: Position { x: 1.0, y: 2.0 },
+        velocity: Velocity { _x: 3.0 },
+        player: Player,
+    });
+    assert!(bob.has(id::<Player>()));
+    let pos = unsafe { bob.get::<Position>() }.unwrap();
+    assert_eq!((pos.x, pos.y), (1.0, 2.0));
+    assert_eq!(unsafe { bob.get::<Velocity>() }.unwrap()._x, 3.0);
+    bob.remove_bundle::<PlayerBundle>();
+    assert!(!bob.has(id::<Position>()));
+    assert!(!bob.has(id::<Player>()));
+
+    //bundles with unnamed fields
+    let entities = world.spawn_batch(3, |i| {
+        Moving(
+            Position {
+                x: i as f32,
+                y: 0.0,
+            },
+            Velocity { _x: 1.0 },
+        )
+    });
+    let pos = unsafe { world.view(entities[2]).get::<Position>() }.unwrap();
+    assert_eq!(pos.x, 2.0);
+}
+
+#[test]
+fn derive_layout_test() {
+    //the same fields spelled differently
+    assert_eq!(Stats::LAYOUT_HASH, StatsAlias::LAYOUT_HASH);
+    //renamed fields
+    assert_ne!(Stats::LAYOUT_HASH, StatsRenamed::LAYOUT_HASH);
+    //fields of another type with the same size
+    assert_ne!(Stats::LAYOUT_HASH, StatsRetyped::LAYOUT_HASH);
+    assert_ne!(Stats::LAYOUT_HASH, 0);
+}
@@ -0,0 +1,107 @@
+use std::ops::ControlFlow;
+
+use crate::{
+    component::{Component, id::id},
+    flecs::Wildcard,
+    world::World,
+};
+
+struct Likes;
+
+impl Component for Likes {}
+
+struct Health {
+    value: u32,
+}
+
+impl Component for Health {}
+
+#[test]
+fn each_id_test() {
+    let mut world = World::new();
+    world.component::<Likes>(c"Likes");
+    world.component::<Health>(c"Health");
+
+    let alice = world.entity_named(c"alice");
+    let bob = world.entity_named(c"bob");
+    let cecil = world.entity_named(c"cecil");
+    alice.add((id::<Likes>(), bob));
+    bob.add((id::<Likes>(), cecil));
+    cecil.add((id::<Likes>(), alice));
+    cecil.add((id::<Likes>(), bob));
+    for (i, entity) in [alice, bob].into_iter().enumerate() {
+        entity.set_comp(Health { value: i as u32 });
+    }
+
+    //components
+    let mut total = 0;
+    world.each_id(id::<Health>(), |entity| {
+        total += unsafe { entity.get::<Health>() }.unwrap().value + 1;
+        ControlFlow::Continue(())
+    });
+    assert_eq!(total, 3);
+
+    //exact pairs
+    let likes_bob: Vec<_> = world.entities_with((id::<Likes>(), bob)).collect();
+    assert_eq!(likes_bob.len(), 2);
+    assert!(likes_bob.contains(&alice) && likes_bob.contains(&cecil));
+
+    //wildcard pairs yield every entity once
+    let mut likers: Vec<_> = world
+        .entities_with((id::<Likes>(), Wildcard))
+        .map(|entity| entity.id())
+        .collect();
+    likers.sort();
+    let mut expected = vec![alice.id(), bob.id(), cecil.id()];
+    expected.sort();
+    assert_eq!(likers, expected);
+
+    //early exit
+    let mut visited = 0;
+    world.each_id((id::<Likes>(), Wildcard), |_| {
+        visited += 1;
+        ControlFlow::Break(())
+    });
+    assert_eq!(visited, 1);
+    assert_eq!(world.entities_with((Wildcard, alice)).count(), 1);
+}
+
+#[test]
+fn each_id_mutation_test() {
+    let mut world = World::new();
+    world.component::<Likes>(c"Likes");
+    world.component::<Health>(c"Health");
+    let entities: Vec<_> = (0..8).map(|_| world.entity()).collect();
+    for (i, entity) in entities.iter().enumerate() {
+        entity.set_comp(Health { value: i as u32 });
+    }
+
+    //entities move to another table while it is being iterated
+    let mut visited = 0;
+    world.each_id(id::<Health>(), |entity| {
+        entity.add(id::<Likes>());
+        visited += 1;
+        ControlFlow::Continue(())
+    });
+    assert_eq!(visited, 8);
+    assert!(entities.iter().all(|entity| entity.has(id::<Likes>())));
+
+    //changes are deferred until the iterator is dropped
+    let mut visited = 0;
+    for entity in world.entities_with(id::<Likes>()) {
+        entity.remove(id::<Health>());
+        assert!(entity.has(id::<Health>()));
+        visited += 1;
+    }
+    assert_eq!(visited, 8);
+    assert_eq!(world.entities_with(id::<Health>()).count(), 0);
+
+    //entities are yielded lazily, even if the iterator is dropped early
+    let mut iter = world.entities_with(id::<Likes>());
+    let first = iter.next().unwrap();
+    first.set_comp(Health { value: 0 });
+    assert!(!first.has(id::<Health>()));
+    drop(iter);
+    assert!(first.has(id::<Health>()));
+    assert_eq!(world.entities_with(id::<Health>()).count(), 1);
+}
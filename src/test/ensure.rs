@@ -0,0 +1,62 @@
+use crate::{
+    component::{Component, id::id},
+    world::World,
+};
+
+#[derive(Default)]
+struct Health {
+    points: u32,
+    history: Vec<u32>,
+}
+
+impl Component for Health {
+    const DEFAULT: Option<fn() -> Self> = Some(Self::default);
+}
+
+struct Velocity {
+    _x: f32,
+}
+
+impl Component for Velocity {}
+
+#[test]
+fn ensure_test() {
+    let mut world = World::new();
+    world.component::<Health>(c"Health");
+
+    //added components are constructed by default
+    let alice = world.entity();
+    alice.add(id::<Health>());
+    let health = unsafe { alice.get::<Health>() }.unwrap();
+    assert_eq!(health.points, 0);
+    assert!(health.history.is_empty());
+
+    //ensure adds missing components and keeps existing ones
+    let bob = world.entity();
+    unsafe { bob.ensure::<Health>() }.points = 10;
+    let health = unsafe { bob.ensure::<Health>() };
+    health.history.push(health.points);
+    let health = unsafe { bob.get::<Health>() }.unwrap();
+    assert_eq!(
+        (health.points, health.history.as_slice()),
+        (10, [10].as_slice())
+    );
+
+    //singletons
+    world.singleton_add(id::<Health>());
+    assert!(world.singleton_exists(id::<Health>()));
+    unsafe { world.singleton_ensure::<Health>() }.points = 3;
+    assert_eq!(
+        unsafe { world.singleton_get::<Health>() }.unwrap().points,
+        3
+    );
+}
+
+#[test]
+#[should_panic]
+fn add_uninitialized_test() {
+    let mut world = World::new();
+    let velocity = world.component::<Velocity>(c"Velocity").id();
+    //there is no way to construct the velocity, adding it by id::<Velocity>() does not compile
+    world.entity().add(velocity);
+}
@@ -0,0 +1,95 @@
+use std::{cell::Cell, rc::Rc};
+
+use crate::{
+    component::{Component, id::id},
+    query::term::TermBuilder,
+    world::World,
+};
+
+struct Damaged {
+    amount: usize,
+}
+
+struct Healed;
+
+struct Health {
+    hp: usize,
+}
+
+impl Component for Damaged {}
+impl Component for Healed {}
+impl Component for Health {}
+
+#[test]
+fn event_test() {
+    let mut world = World::new();
+    //register components and events
+    world.component::<Health>(c"Health");
+    world.component::<Damaged>(c"Damaged");
+    world.component::<Healed>(c"Healed");
+    //create entities
+    let alice = world.entity_named(c"alice");
+    alice.set_comp(Health { hp: 100 });
+    let bob = world.entity_named(c"bob");
+    bob.set_comp(Health { hp: 50 });
+
+    //observe the damage
+    let damage = Rc::new(Cell::new(0));
+    world
+        .observer()
+        .with(id::<Health>())
+        .event(id::<Damaged>())
+        .build({
+            let damage = damage.clone();
+            move |iter| {
+                let event = iter.event_param::<Damaged>().unwrap();
+                let mut health = unsafe { iter.get::<Health>(0) }.unwrap();
+                for ent in 0..iter.count() {
+                    health[ent].hp -= event.amount;
+                    damage.set(damage.get() + event.amount);
+                }
+            }
+        });
+    //observe the healing
+    let healed = Rc::new(Cell::new(0));
+    world
+        .observer()
+        .with(id::<Health>())
+        .event(id::<Healed>())
+        .build({
+            let healed = healed.clone();
+            move |iter| {
+                assert!(iter.event_param::<Damaged>().is_none());
+                healed.set(healed.get() + iter.count());
+            }
+        });
+
+    //emit
+    world
+        .event::<Damaged>()
+        .id(id::<Health>())
+        .entity(alice)
+        .emit(Damaged { amount: 30 });
+    assert_eq!(damage.get(), 30);
+    assert_eq!(unsafe { alice.get::<Health>() }.unwrap().hp, 70);
+    assert_eq!(unsafe { bob.get::<Health>() }.unwrap().hp, 50);
+    world
+        .event::<Healed>()
+        .id(id::<Health>())
+        .entity(bob)
+        .emit(Healed);
+    assert_eq!(healed.get(), 1);
+    assert_eq!(damage.get(), 30);
+
+    //enqueue
+    world.defer_begin();
+    world
+        .event::<Damaged>()
+        .id(id::<Health>())
+        .entity(bob)
+        .enqueue(Damaged { amount: 5 });
+    assert_eq!(damage.get(), 30);
+    world.defer_end();
+    assert_eq!(damage.get(), 35);
+    assert_eq!(unsafe { bob.get::<Health>() }.unwrap().hp, 45);
+}
@@ -0,0 +1,91 @@
+use crate::{
+    component::{Component, id::id},
+    world::World,
+};
+
+struct Position {
+    x: f32,
+    y: f32,
+}
+
+impl Component for Position {}
+
+struct Velocity {
+    x: f32,
+}
+
+impl Component for Velocity {}
+
+#[test]
+fn guard_test() {
+    let mut world = World::new();
+    world.component::<Position>(c"Position");
+    world.component::<Velocity>(c"Velocity");
+    let alice = world.entity();
+    alice.set_comp(Position { x: 1.0, y: 2.0 });
+    assert!(alice.get_ref::<Velocity>().is_none());
+
+    //shared borrows may overlap
+    {
+        let first = alice.get_ref::<Position>().unwrap();
+        let second = alice.get_ref::<Position>().unwrap();
+        assert_eq!((first.x, second.y), (1.0, 2.0));
+    }
+
+    //exclusive borrows write in place
+    {
+        let mut position = alice.get_mut_guard::<Position>().unwrap();
+        position.x = 3.0;
+        //other components may be borrowed meanwhile
+        let bob = world.entity();
+        bob.set_comp(Velocity { x: 4.0 });
+        assert!(bob.get_ref::<Velocity>().is_none());
+    }
+    assert_eq!(alice.get_ref::<Position>().unwrap().x, 3.0);
+
+    //structural changes wait for the guards to be dropped
+    {
+        let position = alice.get_ref::<Position>().unwrap();
+        alice.set_comp(Velocity { x: 5.0 });
+        alice.remove(id::<Position>());
+        assert!(!alice.has(id::<Velocity>()));
+        assert_eq!(position.x, 3.0);
+    }
+    assert!(!alice.has(id::<Position>()));
+    assert_eq!(alice.get_ref::<Velocity>().unwrap().x, 5.0);
+}
+
+#[test]
+#[should_panic]
+fn guard_aliasing_test() {
+    let mut world = World::new();
+    world.component::<Position>(c"Position");
+    let alice = world.entity();
+    alice.set_comp(Position { x: 1.0, y: 2.0 });
+    let _position = alice.get_ref::<Position>().unwrap();
+    let _aliased = alice.get_mut_guard::<Position>();
+}
+
+#[test]
+#[should_panic(expected = "borrowed by a guard")]
+fn guard_query_test() {
+    let mut world = World::new();
+    world.component::<Position>(c"Position");
+    let alice = world.entity();
+    alice.set_comp(Position { x: 1.0, y: 2.0 });
+    let query = world.query_typed::<(&mut Position,)>().build();
+    //the query would hand out the borrowed position mutably
+    let position = alice.get_ref::<Position>().unwrap();
+    query.each(|(moved,)| moved.x += position.y);
+}
+
+#[test]
+#[should_panic(expected = "borrowed by a guard")]
+fn guard_progress_test() {
+    let mut world = World::new();
+    world.component::<Position>(c"Position");
+    let alice = world.entity();
+    alice.set_comp(Position { x: 1.0, y: 2.0 });
+    let _position = alice.get_mut_guard::<Position>().unwrap();
+    world.progress();
+}
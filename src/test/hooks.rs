@@ -0,0 +1,124 @@
+use std::{cell::Cell, rc::Rc};
+
+use crate::{
+    component::{Component, id::id},
+    world::World,
+};
+
+#[derive(Clone, Default)]
+struct Label {
+    text: String,
+}
+
+struct Marker {
+    _value: u32,
+}
+
+#[derive(Clone)]
+struct Handle {
+    name: String,
+}
+
+impl Component for Label {}
+impl Component for Marker {}
+impl Component for Handle {}
+
+#[test]
+fn hooks_test() {
+    let mut world = World::new();
+    //register components
+    world.component::<Marker>(c"Marker");
+    let added = Rc::new(Cell::new(0));
+    let set = Rc::new(Cell::new(0));
+    let removed = Rc::new(Cell::new(0));
+    let label = world
+        .component::<Label>(c"Label")
+        .hooks::<Label>()
+        .ctor_default()
+        .moves()
+        .clone_copy()
+        .on_add({
+            let added = added.clone();
+            move |_, label| {
+                //constructed by default
+                assert!(label.text.is_empty());
+                added.set(added.get() + 1);
+            }
+        })
+        .on_set({
+            let set = set.clone();
+            move |entity, label| {
+                assert_eq!(entity.name(), c"alice");
+                assert_eq!(label.text, "hello");
+                set.set(set.get() + 1);
+            }
+        })
+        .on_remove({
+            let removed = removed.clone();
+            move |_, _| removed.set(removed.get() + 1)
+        })
+        .build()
+        .id();
+
+    //hooks are invoked
+    let alice = world.entity_named(c"alice");
+    alice.set_comp(Label {
+        text: "hello".to_owned(),
+    });
+    assert_eq!(added.get(), 1);
+    assert_eq!(set.get(), 1);
+
+    //components survive table moves
+    alice.set_comp(Marker { _value: 3 });
+    let bob = world.entity_named(c"bob");
+    bob.set_comp(Marker { _value: 4 });
+    //constructed by the hook, without Component::DEFAULT
+    bob.add(label);
+    assert_eq!(added.get(), 2);
+    assert_eq!(
+        unsafe { alice.get::<Label>() }.unwrap().text,
+        "hello".to_owned()
+    );
+    assert!(unsafe { bob.get::<Label>() }.unwrap().text.is_empty());
+
+    //removal
+    alice.remove(id::<Label>());
+    bob.delete();
+    assert_eq!(removed.get(), 2);
+}
+
+#[test]
+#[should_panic]
+fn hooks_mismatch_test() {
+    let mut world = World::new();
+    world.component::<Marker>(c"Marker");
+    //label hooks must not be installed on marker storage
+    world
+        .component::<Label>(c"Label")
+        .hooks::<Marker>()
+        .moves()
+        .build();
+}
+
+#[test]
+fn component_clone_test() {
+    let mut world = World::new();
+    //no Default needed
+    world.component_clone::<Handle>(c"Handle");
+    let alice = world.entity();
+    alice.set_comp(Handle {
+        name: "alice".to_owned(),
+    });
+    let handle = unsafe { alice.get::<Handle>() }.unwrap().clone();
+    assert_eq!(handle.name, "alice");
+
+    //copied into new and existing components
+    let bob = world.entity();
+    bob.set_comp(handle.clone());
+    bob.set_comp(Handle {
+        name: "bob".to_owned(),
+    });
+    assert_eq!(unsafe { bob.get::<Handle>() }.unwrap().name, "bob");
+    alice.delete();
+    bob.delete();
+}
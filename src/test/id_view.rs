@@ -0,0 +1,67 @@
+use crate::{
+    component::{
+        Component,
+        id::{IdFetcher, id},
+        id_view::IdView,
+    },
+    flecs::{
+        Wildcard,
+        id_flags::{AutoOverride, Toggle},
+    },
+    world::World,
+};
+
+struct Likes;
+
+impl Component for Likes {}
+
+struct Amount {
+    value: u32,
+}
+
+impl Component for Amount {}
+
+#[test]
+fn id_view_test() {
+    let mut world = World::new();
+    world.component::<Likes>(c"Likes");
+    world.component::<Amount>(c"Amount");
+    let likes = id::<Likes>().retrieve_id(&world);
+    let amount = id::<Amount>().retrieve_id(&world);
+    let bob = world.entity_named(c"bob");
+
+    //pairs decompose into their elements
+    let pair = IdView::pair(&world, id::<Likes>(), bob);
+    assert!(pair.is_pair());
+    assert!(!pair.is_wildcard());
+    assert_eq!(pair.first().unwrap().id(), likes);
+    assert_eq!(pair.second(), Some(bob));
+    assert_eq!(pair.to_string(), "(Likes,bob)");
+    assert!(pair.type_id().is_none());
+    assert_eq!(world.id_view((id::<Likes>(), bob)), pair);
+
+    //ids are first class
+    let alice = world.entity_named(c"alice");
+    alice.add(pair);
+    assert!(alice.has(pair));
+    assert!(alice.has(IdView::pair(&world, id::<Likes>(), Wildcard)));
+    assert!(IdView::pair(&world, id::<Likes>(), Wildcard).is_wildcard());
+
+    //data pairs know their type
+    alice.set_first(Amount { value: 3 }, bob);
+    let data_pair = world.id_view((id::<Amount>(), bob));
+    assert_eq!(data_pair.type_id().unwrap().id(), amount);
+    assert_eq!(data_pair.type_info().unwrap().size, 4);
+    assert_eq!(unsafe { alice.get_first::<Amount>(bob) }.unwrap().value, 3);
+
+    //flagged ids
+    let component = world.id_view(id::<Amount>());
+    assert!(!component.is_pair());
+    assert_eq!(component.to_string(), "Amount");
+    assert!(!component.has_flag(Toggle));
+    let toggled = IdView::new(&world, Toggle.retrieve_id(&world) | amount);
+    assert!(toggled.has_flag(Toggle));
+    assert!(!toggled.has_flag(AutoOverride));
+    assert_eq!(toggled.entity().unwrap().id(), amount);
+    assert_eq!(toggled.to_string(), "TOGGLE|Amount");
+}
@@ -0,0 +1,71 @@
+use std::rc::Rc;
+
+use crate::{
+    component::{Component, id::id},
+    world::World,
+};
+
+struct Position {
+    x: f32,
+    y: f32,
+}
+
+impl Component for Position {}
+
+struct Velocity {
+    x: f32,
+}
+
+impl Component for Velocity {}
+
+struct Frozen;
+
+impl Component for Frozen {}
+
+/// Keeps a reference count, to check the data is owned exactly once.
+struct Tracked(Rc<usize>);
+
+impl Component for Tracked {}
+
+#[test]
+fn insert_test() {
+    let mut world = World::new();
+    world.component::<Position>(c"Position");
+    world.component::<Velocity>(c"Velocity");
+    world.component::<Frozen>(c"Frozen");
+    world.component::<Tracked>(c"Tracked");
+
+    //create an entity with a bundle
+    let entity = world.entity_with((Position { x: 1.0, y: 2.0 }, Frozen));
+    assert!(entity.has(id::<Frozen>()));
+    let position = unsafe { entity.get::<Position>() }.unwrap();
+    assert_eq!((position.x, position.y), (1.0, 2.0));
+
+    //existing components are overwritten, new ones are added
+    entity.insert((Velocity { x: 3.0 }, Position { x: 4.0, y: 5.0 }));
+    let position = unsafe { entity.get::<Position>() }.unwrap();
+    assert_eq!((position.x, position.y), (4.0, 5.0));
+    assert_eq!(unsafe { entity.get::<Velocity>() }.unwrap().x, 3.0);
+    assert!(entity.has(id::<Frozen>()));
+
+    //remove only some of the components, missing ones are ignored
+    entity.remove_bundle::<(Position, Frozen, Tracked)>();
+    assert!(!entity.has(id::<Position>()));
+    assert!(!entity.has(id::<Frozen>()));
+    assert!(entity.has(id::<Velocity>()));
+
+    //components with drop are owned by the world
+    let counter = Rc::new(0);
+    let tracked = world.entity_with((Tracked(counter.clone()),));
+    assert_eq!(Rc::strong_count(&counter), 2);
+    tracked.remove_bundle::<(Tracked,)>();
+    assert_eq!(Rc::strong_count(&counter), 1);
+}
+
+#[test]
+#[should_panic(expected = "contains a component twice")]
+fn insert_duplicate_test() {
+    let mut world = World::new();
+    world.component::<Frozen>(c"Frozen");
+    world.entity().insert((Frozen, Frozen));
+}
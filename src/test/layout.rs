@@ -0,0 +1,81 @@
+use std::cell::Cell;
+
+use crate::{
+    component::{Component, ComponentView, id::id, layout::ComponentLayout},
+    world::World,
+};
+
+/// Health as seen by one binary.
+struct Health {
+    _hp: u32,
+}
+
+/// Health as seen by another binary, with a different layout.
+struct HealthV2 {
+    _hp: u64,
+    _max: u64,
+}
+
+/// Same layout as Health.
+struct HealthAlias {
+    _hp: u32,
+}
+
+/// Tag, which must not take the symbol of a data component.
+struct Poisoned;
+
+/// Component with a stable symbol.
+struct Mana {
+    value: u32,
+}
+
+impl Component for Health {}
+impl Component for HealthV2 {}
+impl Component for HealthAlias {}
+impl Component for Poisoned {}
+impl Component for Mana {
+    const SYMBOL: Option<&'static std::ffi::CStr> = Some(c"game.Mana");
+
+    fn on_register(_component: ComponentView<'_>) {
+        MANA_REGISTERED.set(MANA_REGISTERED.get() + 1);
+    }
+}
+
+thread_local! {
+    /// How many times Mana was set up on registration.
+    static MANA_REGISTERED: Cell<u32> = const { Cell::new(0) };
+}
+
+#[test]
+fn layout_test() {
+    let mut world = World::new();
+    let health = world.component::<Health>(c"Health").id();
+    world.register::<Mana>();
+    world.register::<Mana>();
+    let alice = world.entity_named(c"alice");
+    alice.set_comp(Mana { value: 7 });
+
+    //the fingerprint is stored on the component
+    let layout = unsafe { world.view(health).get::<ComponentLayout>() }.unwrap();
+    assert_eq!(*layout, ComponentLayout::of::<Health>());
+
+    //another binary attaching to the same world
+    let mut other = unsafe { World::from_ptr(world.ptr()) };
+    let err = other.try_component::<HealthV2>(c"Health").unwrap_err();
+    assert_eq!(err.registered, ComponentLayout::of::<Health>());
+    assert_eq!(err.requested, ComponentLayout::of::<HealthV2>());
+    assert_eq!(
+        other.try_component::<HealthAlias>(c"Health").unwrap().id(),
+        health
+    );
+    assert!(other.try_tag::<Poisoned>(c"Health").is_err());
+
+    //components with a symbol are found without registering them again
+    let alice = other.lookup(c"alice").unwrap();
+    assert_eq!(unsafe { alice.get::<Mana>() }.unwrap().value, 7);
+    assert!(alice.has(id::<Mana>()));
+
+    //components are set up only when they are registered for the first time
+    other.register::<Mana>();
+    assert_eq!(MANA_REGISTERED.get(), 1);
+}
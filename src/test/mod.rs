@@ -1,6 +1,33 @@
 mod basic;
+mod bundle;
+mod change;
 mod child;
+mod comp_ref;
+#[cfg(feature = "derive")]
+mod derive;
 mod drop;
+mod each_id;
+mod ensure;
+mod event;
+mod guard;
+mod hooks;
+mod id_view;
+mod insert;
+mod layout;
+mod modify;
+mod module;
+mod observer;
+mod panic;
+#[cfg(feature = "plugin")]
+mod plugin;
+#[cfg(feature = "plugin")]
+mod plugin_reload;
+mod prefab;
 mod query;
+mod relationship;
 mod singleton;
 mod system;
+mod system_handle;
+mod system_state;
+mod term;
+mod typed;
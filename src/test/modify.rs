@@ -0,0 +1,112 @@
+use std::{cell::Cell, rc::Rc};
+
+use crate::{
+    component::{Component, id::id},
+    flecs::Inherit,
+    world::World,
+};
+
+#[derive(Default)]
+struct Health {
+    points: u32,
+}
+
+impl Component for Health {}
+
+struct Damage;
+
+impl Component for Damage {}
+
+#[derive(Default)]
+struct Armor {
+    points: u32,
+}
+
+impl Component for Armor {}
+
+#[test]
+fn modify_test() {
+    let mut world = World::new();
+    let set = Rc::new(Cell::new(0));
+    world
+        .component::<Health>(c"Health")
+        .hooks::<Health>()
+        .ctor_default()
+        .on_set({
+            let set = set.clone();
+            move |_, _| set.set(set.get() + 1)
+        })
+        .build();
+    world.component::<Damage>(c"Damage");
+
+    //modify notifies, adding the component if missing
+    let alice = world.entity();
+    let old = alice.modify(|health: &mut Health| {
+        let old = health.points;
+        health.points = 10;
+        old
+    });
+    assert_eq!(old, 0);
+    assert_eq!(set.get(), 1);
+    assert_eq!(unsafe { alice.get::<Health>() }.unwrap().points, 10);
+
+    //writes through get_mut are notified explicitly
+    let mut bob = world.entity();
+    bob.set_comp(Health { points: 1 });
+    assert_eq!(set.get(), 2);
+    unsafe { bob.get_mut::<Health>() }.unwrap().points = 2;
+    assert_eq!(set.get(), 2);
+    bob.modified(id::<Health>());
+    assert_eq!(set.get(), 3);
+
+    //pairs
+    alice.modify_first(id::<Damage>(), |health: &mut Health| health.points = 5);
+    alice.modify_second(id::<Damage>(), |health: &mut Health| health.points = 7);
+    let first = unsafe { alice.get_first::<Health>(id::<Damage>()) }.unwrap();
+    let second = unsafe { alice.get_second::<Health>(id::<Damage>()) }.unwrap();
+    assert_eq!((first.points, second.points), (5, 7));
+    assert_eq!(unsafe { alice.get::<Health>() }.unwrap().points, 10);
+}
+
+#[test]
+#[should_panic(expected = "does not hold data of type")]
+fn modify_pair_type_test() {
+    let mut world = World::new();
+    world.component::<Health>(c"Health");
+    world.component::<Armor>(c"Armor");
+    //the pair holds health, its first element
+    let alice = world.entity();
+    alice.modify_second(id::<Health>(), |armor: &mut Armor| armor.points += 1);
+}
+
+#[test]
+fn modify_guard_test() {
+    let mut world = World::new();
+    world.component::<Health>(c"Health");
+    world
+        .component::<Armor>(c"Armor")
+        .hooks::<Armor>()
+        .ctor_default()
+        .build();
+    let alice = world.entity();
+    alice.set_comp(Health { points: 4 });
+
+    //adding the missing armor waits for the guard of health
+    let health = alice.get_ref::<Health>().unwrap();
+    alice.modify(|armor: &mut Armor| armor.points = 3);
+    assert_eq!(health.points, 4);
+    drop(health);
+    assert_eq!(unsafe { alice.get::<Armor>() }.unwrap().points, 3);
+}
+
+#[test]
+#[should_panic(expected = "without data")]
+fn modify_inherited_test() {
+    let mut world = World::new();
+    world.component::<Armor>(c"Armor").on_instantiate(Inherit);
+    let knight = world.prefab(c"Knight");
+    knight.set_comp(Armor { points: 2 });
+    //inherited armor would be overridden without a constructor
+    let alice = world.instantiate(knight);
+    alice.modify(|armor: &mut Armor| armor.points += 1);
+}
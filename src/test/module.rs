@@ -0,0 +1,97 @@
+use std::{
+    ffi::CStr,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+    component::{
+        Component,
+        id::{IdFetcher, id},
+    },
+    flecs::{ChildOf, Module as ModuleTag},
+    module::Module,
+    query::term::TermBuilder,
+    world::World,
+};
+
+struct Velocity {
+    value: f32,
+}
+
+impl Component for Velocity {
+    const SYMBOL: Option<&'static CStr> = Some(c"Velocity");
+}
+
+/// Counts imports, to check modules are imported once.
+static IMPORTS: AtomicUsize = AtomicUsize::new(0);
+
+struct Movement;
+
+impl Module for Movement {
+    const NAME: &'static CStr = c"GameMovement";
+
+    fn import(world: &mut World) {
+        IMPORTS.fetch_add(1, Ordering::Relaxed);
+        world.register::<Velocity>();
+        world
+            .system()
+            .with(id::<Velocity>())
+            .build_named(c"accelerate", |iter| {
+                let mut velocity = unsafe { iter.get::<Velocity>(0) }.unwrap();
+                for i in 0..iter.count() {
+                    velocity[i].value += 1.0;
+                }
+            });
+    }
+}
+
+/// Module nested in the path of another one.
+struct Physics;
+
+impl Module for Physics {
+    const NAME: &'static CStr = c"GameMovementPhysics";
+
+    fn import(_world: &mut World) {}
+}
+
+#[test]
+fn module_test() {
+    let mut world = World::new();
+    let module = world.import::<Movement>().id();
+    assert!(world.view(module).has(ModuleTag));
+    assert_eq!(world.module::<Movement>().unwrap().id(), module);
+
+    //imported once
+    assert_eq!(world.import::<Movement>().id(), module);
+    assert_eq!(IMPORTS.load(Ordering::Relaxed), 1);
+
+    //everything is scoped under the module and discoverable by path
+    assert_eq!(world.lookup(c"game.movement").unwrap().id(), module);
+    let velocity = world.lookup(c"game.movement.Velocity").unwrap();
+    assert_eq!(velocity.id(), id::<Velocity>().retrieve_id(&world));
+    let system = world.lookup(c"game.movement.accelerate").unwrap();
+    assert!(system.has((ChildOf, module)));
+
+    //systems of the module run
+    let entity = world.entity();
+    entity.set_comp(Velocity { value: 0.0 });
+    world.progress();
+    assert_eq!(unsafe { entity.get::<Velocity>() }.unwrap().value, 1.0);
+}
+
+#[test]
+fn nested_module_test() {
+    let mut world = World::new();
+    assert!(world.module::<Physics>().is_none());
+    let physics = world.import::<Physics>().id();
+
+    //found, although its symbol is its path
+    assert_eq!(world.module::<Physics>().unwrap().id(), physics);
+    assert_eq!(
+        world.lookup(c"game.movement.physics").unwrap().id(),
+        physics
+    );
+    assert!(world.module::<Movement>().is_none());
+    let parent = world.lookup(c"game.movement").unwrap().id();
+    assert!(world.view(physics).has((ChildOf, parent)));
+}
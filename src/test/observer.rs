@@ -0,0 +1,106 @@
+use std::{cell::Cell, rc::Rc};
+
+use crate::{
+    component::{Component, id::id},
+    flecs::{OnRemove, OnSet},
+    query::term::TermBuilder,
+    world::World,
+};
+
+struct Health {
+    hp: usize,
+}
+
+impl Component for Health {}
+
+#[test]
+fn observer_test() {
+    let mut world = World::new();
+    //register components
+    world.component::<Health>(c"Health");
+    //an entity existing before the observer
+    let alice = world.entity_named(c"alice");
+    alice.set_comp(Health { hp: 5 });
+
+    //observe sets
+    let total = Rc::new(Cell::new(0));
+    let observer = world
+        .observer()
+        .with(id::<Health>())
+        .event(OnSet)
+        .yield_existing()
+        .build_named(c"health_observer", {
+            let total = total.clone();
+            move |iter| {
+                //the world must be accessible from observers
+                let world = iter.world();
+                assert!(world.lookup(c"health_observer").is_some());
+                let health = unsafe { iter.get::<Health>(0) }.unwrap();
+                for ent in 0..iter.count() {
+                    total.set(total.get() + health[ent].hp);
+                }
+            }
+        });
+    assert_eq!(
+        world.lookup(c"health_observer").unwrap().id(),
+        observer.id()
+    );
+    //yield existing
+    assert_eq!(total.get(), 5);
+    //new sets
+    let bob = world.entity_named(c"bob");
+    bob.set_comp(Health { hp: 10 });
+    assert_eq!(total.get(), 15);
+
+    //disabled observers are not invoked
+    observer.disable();
+    assert!(!observer.is_enabled());
+    bob.set_comp(Health { hp: 100 });
+    assert_eq!(total.get(), 15);
+    observer.enable();
+    bob.set_comp(Health { hp: 1 });
+    assert_eq!(total.get(), 16);
+
+    //observe removals from an expression
+    let removed = Rc::new(Cell::new(0));
+    world.observer_expr(c"Health").event(OnRemove).build({
+        let removed = removed.clone();
+        move |iter| removed.set(removed.get() + iter.count())
+    });
+    alice.remove(id::<Health>());
+    assert_eq!(removed.get(), 1);
+
+    //deleted observers are not invoked
+    observer.delete();
+    assert!(world.lookup(c"health_observer").is_none());
+    alice.set_comp(Health { hp: 1000 });
+    assert_eq!(total.get(), 16);
+}
+
+#[test]
+fn observer_reentrant_test() {
+    let mut world = World::new();
+    world.component::<Health>(c"Health");
+    //the observer sets the component it observes, invoking itself again
+    let calls = Rc::new(Cell::new(0));
+    world.observer().with(id::<Health>()).event(OnSet).build({
+        let calls = calls.clone();
+        move |iter| {
+            calls.set(calls.get() + 1);
+            let world = iter.world();
+            let health = unsafe { iter.get::<Health>(0) }.unwrap();
+            for ent in 0..iter.count() {
+                if health[ent].hp < 3 {
+                    let entity = world.view(iter.entity(ent).unwrap());
+                    entity.set_comp(Health {
+                        hp: health[ent].hp + 1,
+                    });
+                }
+            }
+        }
+    });
+    let alice = world.entity();
+    alice.set_comp(Health { hp: 0 });
+    assert_eq!(unsafe { alice.get::<Health>() }.unwrap().hp, 3);
+    assert_eq!(calls.get(), 4);
+}
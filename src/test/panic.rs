@@ -0,0 +1,187 @@
+use std::{
+    cell::Cell,
+    env,
+    panic::{AssertUnwindSafe, catch_unwind},
+    process::Command,
+    rc::Rc,
+};
+
+use crate::{
+    component::{Component, id::id},
+    entity::Entity,
+    flecs::{
+        OnSet,
+        pipeline::{OnUpdate, PostUpdate},
+    },
+    query::{QueryCacheKind, term::TermBuilder},
+    world::World,
+};
+
+struct Fuse {
+    lit: bool,
+}
+
+impl Component for Fuse {}
+
+struct Rank {
+    value: u32,
+}
+
+impl Component for Rank {}
+
+/// Panics when dropped while armed.
+struct Mine {
+    armed: bool,
+}
+
+impl Component for Mine {}
+
+impl Drop for Mine {
+    fn drop(&mut self) {
+        if self.armed {
+            panic!("mine");
+        }
+    }
+}
+
+/// Panics when cloned.
+#[derive(Default)]
+struct Cursed {
+    _value: u8,
+}
+
+impl Clone for Cursed {
+    fn clone(&self) -> Self {
+        panic!("cursed");
+    }
+}
+
+impl Component for Cursed {}
+
+#[test]
+fn panic_test() {
+    let mut world = World::new();
+    //register components
+    world.component::<Fuse>(c"Fuse");
+    let bomb = world.entity_named(c"bomb");
+    bomb.set_comp(Fuse { lit: false });
+
+    //system panicking when the fuse is lit
+    world
+        .system()
+        .with(id::<Fuse>())
+        .kind(OnUpdate)
+        .build(|iter| {
+            let fuse = unsafe { iter.get::<Fuse>(0) }.unwrap();
+            if fuse[0].lit {
+                panic!("boom");
+            }
+        });
+    //system after it
+    let after = Rc::new(Cell::new(0));
+    world.system().with(id::<Fuse>()).kind(PostUpdate).build({
+        let after = after.clone();
+        move |_| after.set(after.get() + 1)
+    });
+
+    //nothing happens
+    assert!(world.try_progress().is_ok());
+    assert_eq!(after.get(), 1);
+
+    //the panic is returned and the rest of the frame skipped
+    bomb.set_comp(Fuse { lit: true });
+    let payload = world.try_progress().unwrap_err();
+    assert_eq!(payload.downcast_ref::<&str>(), Some(&"boom"));
+    assert_eq!(after.get(), 1);
+
+    //progress resumes the panic
+    let result = catch_unwind(AssertUnwindSafe(|| world.progress()));
+    assert!(result.is_err());
+
+    //the world keeps working
+    bomb.set_comp(Fuse { lit: false });
+    assert!(world.try_progress().is_ok());
+    assert_eq!(after.get(), 2);
+
+    //panics of observers are resumed by the operation triggering them
+    world.observer().with(id::<Fuse>()).event(OnSet).build(|_| {
+        panic!("observed");
+    });
+    let result = catch_unwind(AssertUnwindSafe(|| bomb.set_comp(Fuse { lit: false })));
+    assert_eq!(
+        result.unwrap_err().downcast_ref::<&str>(),
+        Some(&"observed")
+    );
+}
+
+#[test]
+fn order_by_panic_test() {
+    let mut world = World::new();
+    world.component::<Rank>(c"Rank");
+    let first = world.entity();
+    first.set_comp(Rank { value: 1 });
+    world.entity().set_comp(Rank { value: 2 });
+    assert_eq!(unsafe { first.get::<Rank>() }.unwrap().value, 1);
+    let query = world
+        .query()
+        .with(id::<Rank>())
+        .set_cache(QueryCacheKind::Auto)
+        .order_by::<Rank, _>(|_: Entity, _: &Rank, _: Entity, _: &Rank| -> i32 {
+            panic!("unordered")
+        })
+        .build();
+
+    //the panic is resumed by the iteration
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let mut iter = query.iter();
+        while iter.advance() {}
+    }));
+    assert_eq!(
+        result.unwrap_err().downcast_ref::<&str>(),
+        Some(&"unordered")
+    );
+
+    //it does not leak into other worlds
+    let mut other = World::new();
+    assert!(other.try_progress().is_ok());
+    assert!(world.try_progress().is_ok());
+}
+
+#[test]
+fn dtor_panic_test() {
+    let mut world = World::new();
+    world.component::<Mine>(c"Mine");
+    let field = world.entity();
+    field.set_comp(Mine { armed: true });
+
+    //the panic is resumed by the operation dropping the component
+    let result = catch_unwind(AssertUnwindSafe(|| field.delete()));
+    assert_eq!(result.unwrap_err().downcast_ref::<&str>(), Some(&"mine"));
+
+    //the world keeps working
+    let safe = world.entity();
+    safe.set_comp(Mine { armed: false });
+    safe.delete();
+    assert!(world.try_progress().is_ok());
+}
+
+/// Environment variable making [copy_panic_test] run the aborting part.
+const COPY_PANIC_CHILD: &str = "SIMPLE_FLECS_COPY_PANIC_CHILD";
+
+#[test]
+fn copy_panic_test() {
+    if env::var_os(COPY_PANIC_CHILD).is_some() {
+        let mut world = World::new();
+        world.component_clone::<Cursed>(c"Cursed");
+        //setting copies the component, whose clone panics
+        world.entity().set_comp(Cursed { _value: 0 });
+        return;
+    }
+    //a panicking copy hook aborts, so run it in another process
+    let status = Command::new(env::current_exe().unwrap())
+        .args(["--exact", "test::panic::copy_panic_test", "--nocapture"])
+        .env(COPY_PANIC_CHILD, "1")
+        .status()
+        .unwrap();
+    assert!(!status.success());
+}
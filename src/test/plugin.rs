@@ -0,0 +1,87 @@
+use std::{
+    ffi::CStr,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::{
+    component::{Component, id::id},
+    plugin::Plugin,
+    world::World,
+};
+
+/// Score of the fixture plugin.
+struct Score {
+    value: u32,
+}
+
+impl Component for Score {
+    const SYMBOL: Option<&'static CStr> = Some(c"fixture.Score");
+}
+
+/// Tag of the fixture plugin.
+struct Observed;
+
+impl Component for Observed {
+    const SYMBOL: Option<&'static CStr> = Some(c"fixture.Observed");
+}
+
+/// Builds the fixture plugin and returns the path of its library.
+pub(super) fn build_fixture() -> PathBuf {
+    build_fixture_with(None)
+}
+
+/// Builds the fixture plugin with a feature, each feature has its own target directory.
+pub(super) fn build_fixture_with(feature: Option<&str>) -> PathBuf {
+    let manifest = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/plugin_fixture/Cargo.toml"
+    );
+    let target = match feature {
+        Some(feature) => format!("{}/target/fixtures-{feature}", env!("CARGO_MANIFEST_DIR")),
+        None => format!("{}/target/fixtures", env!("CARGO_MANIFEST_DIR")),
+    };
+    let mut command = Command::new(env!("CARGO"));
+    command.args([
+        "build",
+        "--manifest-path",
+        manifest,
+        "--target-dir",
+        &target,
+    ]);
+    if let Some(feature) = feature {
+        command.args(["--features", feature]);
+    }
+    let status = command.status().expect("failed to run cargo");
+    assert!(status.success(), "failed to build the fixture plugin");
+    Path::new(&target)
+        .join("debug")
+        .join(libloading::library_filename("plugin_fixture"))
+}
+
+#[test]
+fn plugin_test() {
+    let library = build_fixture();
+    let world = World::new();
+    let plugin = unsafe { Plugin::load(&world, &library) }.unwrap();
+    assert_eq!(plugin.name(), c"score_plugin");
+    assert!(world.lookup(c"score_plugin").is_some());
+
+    //everything the plugin created is tracked
+    assert_eq!(plugin.systems(&world).len(), 1);
+    assert_eq!(plugin.observers(&world).len(), 1);
+    assert_eq!(plugin.components(&world).len(), 3);
+
+    //components of the plugin are shared through their symbols
+    let alice = world.entity_named(c"alice");
+    alice.set_comp(Score { value: 0 });
+    assert!(alice.has(id::<Observed>()));
+    world.progress();
+    assert_eq!(unsafe { alice.get::<Score>() }.unwrap().value, 1);
+
+    //unloading removes everything
+    plugin.unload(&world);
+    assert!(world.lookup(c"score_plugin").is_none());
+    assert!(world.lookup_symbol(c"fixture.Score").is_none());
+    assert!(alice.is_alive());
+}
@@ -0,0 +1,112 @@
+use std::{
+    ffi::CStr,
+    fs,
+    panic::{AssertUnwindSafe, catch_unwind},
+};
+
+use crate::{
+    component::{Component, id::id},
+    plugin::Plugin,
+    world::World,
+};
+
+use super::plugin::{build_fixture, build_fixture_with};
+
+/// Score of the fixture plugin.
+struct Score {
+    value: u32,
+}
+
+impl Component for Score {
+    const SYMBOL: Option<&'static CStr> = Some(c"fixture.Score");
+}
+
+/// Bonus of the first version of the fixture plugin.
+struct Bonus {
+    value: u32,
+}
+
+impl Component for Bonus {
+    const SYMBOL: Option<&'static CStr> = Some(c"fixture.Bonus");
+}
+
+#[test]
+fn plugin_reload_test() {
+    //the library is overwritten by new versions, as a rebuild would
+    let library = std::env::temp_dir().join(format!(
+        "{}-reload-{}",
+        std::process::id(),
+        libloading::library_filename("plugin_fixture").to_string_lossy()
+    ));
+    fs::copy(build_fixture(), &library).unwrap();
+    let world = World::new();
+    let mut plugin = unsafe { Plugin::load(&world, &library) }.unwrap();
+
+    let alice = world.entity_named(c"alice");
+    alice.set_comp(Score { value: 0 });
+    alice.set_comp(Bonus { value: 5 });
+    world.progress();
+    let score = world.lookup_symbol(c"fixture.Score").unwrap().id();
+    let bonus = world.lookup_symbol(c"fixture.Bonus").unwrap().id();
+
+    //reloading the same version keeps all data
+    unsafe { plugin.reload(&world) }.unwrap();
+    assert_eq!(plugin.systems(&world).len(), 1);
+    assert_eq!(plugin.observers(&world).len(), 1);
+    assert_eq!(plugin.components(&world).len(), 3);
+    world.progress();
+    assert_eq!(unsafe { alice.get::<Score>() }.unwrap().value, 2);
+    assert_eq!(unsafe { alice.get::<Bonus>() }.unwrap().value, 5);
+
+    //components which cannot be cloned are still set by moving them in
+    let bob = world.entity_named(c"bob");
+    bob.set_comp(Score { value: 7 });
+    assert_eq!(unsafe { bob.get::<Score>() }.unwrap().value, 7);
+
+    //the second version counts faster and changes the layout of bonus
+    fs::copy(build_fixture_with(Some("v2")), &library).unwrap();
+    unsafe { plugin.reload(&world) }.unwrap();
+    assert_eq!(plugin.systems(&world).len(), 1);
+    assert_eq!(world.lookup_symbol(c"fixture.Score").unwrap().id(), score);
+    assert!(!world.view(bonus).is_alive());
+    let new_bonus = world.lookup_symbol(c"fixture.Bonus").unwrap().id();
+    assert!(!alice.has(new_bonus));
+    assert!(alice.has(id::<Score>()));
+    world.progress();
+    assert_eq!(unsafe { alice.get::<Score>() }.unwrap().value, 12);
+
+    plugin.unload(&world);
+    assert!(world.lookup_symbol(c"fixture.Score").is_none());
+    fs::remove_file(library).unwrap();
+}
+
+#[test]
+fn plugin_reload_panic_test() {
+    let library = std::env::temp_dir().join(format!(
+        "{}-reload-panic-{}",
+        std::process::id(),
+        libloading::library_filename("plugin_fixture").to_string_lossy()
+    ));
+    fs::copy(build_fixture(), &library).unwrap();
+    let world = World::new();
+    let mut plugin = unsafe { Plugin::load(&world, &library) }.unwrap();
+    let alice = world.entity_named(c"alice");
+    alice.set_comp(Score { value: 0 });
+
+    //the init of the broken version panics after creating its callbacks
+    fs::copy(build_fixture_with(Some("broken")), &library).unwrap();
+    let result = catch_unwind(AssertUnwindSafe(|| unsafe { plugin.reload(&world) }));
+    assert_eq!(
+        result.unwrap_err().downcast_ref::<&str>(),
+        Some(&"broken plugin")
+    );
+
+    //the old version keeps running
+    assert_eq!(plugin.systems(&world).len(), 1);
+    assert_eq!(plugin.observers(&world).len(), 1);
+    world.progress();
+    assert_eq!(unsafe { alice.get::<Score>() }.unwrap().value, 1);
+
+    plugin.unload(&world);
+    fs::remove_file(library).unwrap();
+}
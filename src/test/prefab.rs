@@ -0,0 +1,127 @@
+use std::rc::Rc;
+
+use crate::{
+    component::{Component, id::id},
+    flecs::{ChildOf, Inherit, IsA, Prefab},
+    world::World,
+};
+
+struct Health {
+    value: u32,
+}
+
+impl Component for Health {}
+
+struct Armor {
+    value: u32,
+}
+
+impl Component for Armor {}
+
+struct Speed {
+    value: u32,
+}
+
+impl Component for Speed {}
+
+/// Keeps a reference count, to check the data is owned exactly once.
+struct Tracked(Rc<usize>);
+
+impl Component for Tracked {}
+
+/// Tracked, but copied to instances by its clone hook.
+#[derive(Clone, Default)]
+struct Shared(Rc<usize>);
+
+impl Component for Shared {}
+
+#[test]
+fn prefab_test() {
+    let mut world = World::new();
+    world.component::<Health>(c"Health");
+    world.component::<Armor>(c"Armor").on_instantiate(Inherit);
+    world.component::<Speed>(c"Speed").on_instantiate(Inherit);
+
+    //prefab with a slot
+    let ship = world.prefab(c"Ship");
+    assert!(ship.has(Prefab));
+    ship.set_comp(Health { value: 100 });
+    ship.set_comp(Armor { value: 5 });
+    ship.set_auto_override(Speed { value: 3 });
+    let turret = world.prefab(c"Turret");
+    turret.add((ChildOf, ship));
+    turret.slot_of(ship);
+
+    let mut instance = world.instantiate(ship);
+    assert!(instance.has((IsA, ship)));
+    assert!(!instance.has(Prefab));
+
+    //armor is inherited, the instance shares it with the prefab
+    assert!(instance.has(id::<Armor>()));
+    assert!(!instance.owns(id::<Armor>()));
+    let inherited = unsafe { instance.get::<Armor>() }.unwrap();
+    assert_eq!(inherited.value, 5);
+    assert!(std::ptr::eq(
+        inherited,
+        unsafe { ship.get::<Armor>() }.unwrap()
+    ));
+
+    //health is overridden, the instance owns a copy
+    assert!(instance.owns(id::<Health>()));
+    unsafe { instance.get_mut::<Health>() }.unwrap().value = 50;
+    assert_eq!(unsafe { instance.get::<Health>() }.unwrap().value, 50);
+    assert_eq!(unsafe { ship.get::<Health>() }.unwrap().value, 100);
+
+    //speed is inherited by default, but auto overridden by the prefab
+    assert!(instance.owns(id::<Speed>()));
+    assert_eq!(unsafe { instance.get::<Speed>() }.unwrap().value, 3);
+
+    //the slot points to the instantiated child
+    let instance_turret = instance.slot(c"Turret").unwrap();
+    assert_ne!(instance_turret, turret);
+    assert!(instance_turret.has((ChildOf, instance)));
+    assert!(instance_turret.has((IsA, turret)));
+
+    //is_a on existing entities
+    let other = world.entity();
+    other.is_a(ship);
+    assert_eq!(unsafe { other.get::<Health>() }.unwrap().value, 100);
+    assert!(world.entity().slot(c"Turret").is_none());
+}
+
+#[test]
+fn prefab_drop_test() {
+    let mut world = World::new();
+    world.component::<Tracked>(c"Tracked");
+    world.component_clone::<Shared>(c"Shared");
+    let counter = Rc::new(0);
+    let ship = world.prefab(c"Ship");
+    ship.set_comp(Tracked(counter.clone()));
+    ship.insert((Shared(counter.clone()),));
+    assert_eq!(Rc::strong_count(&counter), 3);
+
+    //components which cannot be cloned are shared with the prefab
+    let instance = world.instantiate(ship);
+    let other = world.entity();
+    other.is_a(ship);
+    assert!(instance.has(id::<Tracked>()));
+    assert!(!instance.owns(id::<Tracked>()));
+    assert!(!other.owns(id::<Tracked>()));
+
+    //clonable ones are cloned
+    assert!(instance.owns(id::<Shared>()));
+    assert!(other.owns(id::<Shared>()));
+    assert_eq!(Rc::strong_count(&counter), 5);
+
+    drop(world);
+    assert_eq!(Rc::strong_count(&counter), 1);
+}
+
+#[test]
+#[should_panic(expected = "cannot be copied to instances")]
+fn prefab_override_drop_test() {
+    let mut world = World::new();
+    world.component::<Tracked>(c"Tracked");
+    let ship = world.prefab(c"Ship");
+    ship.set_auto_override(Tracked(Rc::new(0)));
+}
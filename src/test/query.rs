@@ -1,5 +1,7 @@
 use crate::{
     component::{Component, id::id},
+    entity::Entity,
+    query::{QueryCacheKind, term::TermBuilder},
     world::World,
 };
 
@@ -163,3 +165,35 @@ fn query_test() {
         }
     }
 }
+
+extern "C" fn by_data(_: Entity, a: &TestData, _: Entity, b: &TestData) -> i32 {
+    a.data.cmp(&b.data) as i32
+}
+
+#[test]
+fn order_by_fn_test() {
+    let mut world = World::new();
+    world.component::<TestData>(c"Data");
+    for data in [5, 2, 11, 3] {
+        world.entity().set_comp(TestData { data });
+    }
+
+    //plain function pointers are handed to flecs as they are
+    let query = world
+        .query()
+        .with(id::<TestData>())
+        .set_cache(QueryCacheKind::Auto)
+        .order_by::<TestData, _>(
+            by_data as extern "C" fn(Entity, &TestData, Entity, &TestData) -> i32,
+        )
+        .build();
+    let mut ordered = Vec::new();
+    let mut iter = query.iter();
+    while iter.advance() {
+        let data = unsafe { iter.get::<TestData>(0) }.unwrap();
+        for row in 0..iter.count() {
+            ordered.push(data[row].data);
+        }
+    }
+    assert_eq!(ordered, [2, 3, 5, 11]);
+}
@@ -0,0 +1,77 @@
+use crate::{
+    component::{
+        Component,
+        id::{IdFetcher, id},
+        id_view::IdView,
+    },
+    flecs::{IsA, id_flags::Toggle},
+    world::World,
+};
+
+struct Likes;
+
+impl Component for Likes {}
+
+struct Position {
+    x: f32,
+}
+
+impl Component for Position {}
+
+#[test]
+fn relationship_test() {
+    let mut world = World::new();
+    world.component::<Likes>(c"Likes");
+    world.component::<Position>(c"Position");
+
+    let alice = world.entity_named(c"alice");
+    let bob = world.entity_named(c"bob");
+    let cecil = world.entity_named(c"cecil");
+    alice.add((id::<Likes>(), bob));
+    alice.add((id::<Likes>(), cecil));
+    alice.set_comp(Position { x: 1.0 });
+
+    //targets
+    assert_eq!(alice.target(id::<Likes>(), 0), Some(bob));
+    assert_eq!(alice.target(id::<Likes>(), 1), Some(cecil));
+    assert_eq!(alice.target(id::<Likes>(), 2), None);
+    let targets: Vec<_> = alice.targets(id::<Likes>()).collect();
+    assert_eq!(targets, vec![bob, cecil]);
+    assert_eq!(alice.relation_count(id::<Likes>()), 2);
+    assert_eq!(bob.relation_count(id::<Likes>()), 0);
+
+    //ids of the entity
+    let ids: Vec<_> = alice.each_id().collect();
+    let position = ids
+        .iter()
+        .find(|id| {
+            id.entity()
+                .is_some_and(|e| e.id() == id::<Position>().retrieve_id(&world))
+        })
+        .unwrap();
+    assert!(!position.is_pair());
+    assert!(!position.is_tag());
+    assert_eq!(position.type_info().unwrap().size, 4);
+    let likes: Vec<_> = ids.iter().filter(|id| id.is_pair()).collect();
+    assert_eq!(likes.len(), 2);
+    assert!(likes.iter().all(|id| id.is_tag()));
+    assert_eq!(
+        likes[0].first().unwrap().id(),
+        id::<Likes>().retrieve_id(&world)
+    );
+    assert_eq!(likes[0].second(), Some(bob));
+    assert!(likes[0].entity().is_none());
+
+    //flags
+    let toggled = IdView::new(&world, Toggle.retrieve_id(&world) | bob.id());
+    assert_eq!(toggled.flags(), Toggle.retrieve_id(&world));
+    assert_eq!(toggled.entity(), Some(bob));
+
+    //the entity a component comes from
+    let instance = world.entity();
+    instance.add((IsA, alice));
+    assert_eq!(instance.target_for(IsA, id::<Position>()), Some(alice));
+    assert_eq!(alice.target_for(IsA, id::<Position>()), Some(alice));
+    assert_eq!(bob.target_for(IsA, id::<Position>()), None);
+    assert_eq!(unsafe { instance.get::<Position>() }.unwrap().x, 1.0);
+}
@@ -0,0 +1,74 @@
+use std::{cell::Cell, rc::Rc};
+
+use crate::{
+    component::{Component, id::id},
+    flecs::pipeline::{OnUpdate, PostUpdate},
+    query::term::TermBuilder,
+    world::World,
+};
+
+struct Counter {
+    count: usize,
+}
+
+impl Component for Counter {}
+
+#[test]
+fn system_handle_test() {
+    let mut world = World::new();
+    //register components
+    world.component::<Counter>(c"Counter");
+    let a = world.entity_named(c"a");
+    a.set_comp(Counter { count: 0 });
+    let b = world.entity_named(c"b");
+    b.set_comp(Counter { count: 0 });
+
+    //count invocations
+    let runs = Rc::new(Cell::new(0));
+    let system = world
+        .system()
+        .with(id::<Counter>())
+        .kind(OnUpdate)
+        .build_named(c"counter_system", {
+            let runs = runs.clone();
+            move |iter| runs.set(runs.get() + iter.count())
+        });
+    assert_eq!(world.lookup(c"counter_system").unwrap().id(), system.id());
+    world.progress();
+    assert_eq!(runs.get(), 2);
+
+    //run outside of the pipeline
+    system.run(0.0);
+    assert_eq!(runs.get(), 4);
+
+    //the query of the system is accessible
+    let query = system.query();
+    let mut iter = query.iter();
+    let mut matched = 0;
+    while iter.advance() {
+        matched += iter.count();
+    }
+    assert_eq!(matched, 2);
+
+    //disabled systems are not progressed
+    system.disable();
+    assert!(!system.is_enabled());
+    world.progress();
+    assert_eq!(runs.get(), 4);
+    system.enable();
+    system.set_kind(PostUpdate);
+    world.progress();
+    assert_eq!(runs.get(), 6);
+
+    //rates and intervals
+    system.set_rate(2);
+    assert_eq!(system.rate(), Some(2));
+    system.set_interval(1.0);
+    assert_eq!(system.interval(), 1.0);
+
+    //deleted systems are gone
+    system.delete();
+    assert!(world.lookup(c"counter_system").is_none());
+    world.progress();
+    assert_eq!(runs.get(), 6);
+}
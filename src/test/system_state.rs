@@ -0,0 +1,74 @@
+use std::{cell::Cell, rc::Rc};
+
+use crate::{
+    component::{Component, id::id},
+    query::term::TermBuilder,
+    world::World,
+};
+
+struct Value {
+    value: usize,
+}
+
+impl Component for Value {}
+
+/// State reporting its drop.
+struct DropFlag {
+    sum: usize,
+    dropped: Rc<Cell<bool>>,
+}
+
+impl Drop for DropFlag {
+    fn drop(&mut self) {
+        self.dropped.set(true);
+    }
+}
+
+#[test]
+fn system_state_test() {
+    let mut world = World::new();
+    //register components
+    world.component::<Value>(c"Value");
+    world.entity().set_comp(Value { value: 2 });
+    world.entity().set_comp(Value { value: 3 });
+
+    //mutable closures
+    let mut invocations = 0;
+    world
+        .system()
+        .with(id::<Value>())
+        .build_named(c"fn_mut_system", move |_iter| {
+            invocations += 1;
+            assert!(invocations <= 2);
+        });
+
+    //system-local state
+    let dropped = Rc::new(Cell::new(false));
+    let seen = Rc::new(Cell::new(0));
+    let system = world.system().with(id::<Value>()).build_named_with_state(
+        c"stateful_system",
+        DropFlag {
+            sum: 0,
+            dropped: dropped.clone(),
+        },
+        {
+            let seen = seen.clone();
+            move |state, iter| {
+                let values = unsafe { iter.get::<Value>(0) }.unwrap();
+                for i in 0..iter.count() {
+                    state.sum += values[i].value;
+                }
+                seen.set(state.sum);
+            }
+        },
+    );
+    world.progress();
+    assert_eq!(seen.get(), 5);
+    world.progress();
+    assert_eq!(seen.get(), 10);
+
+    //state is dropped with the system
+    assert!(!dropped.get());
+    system.delete();
+    assert!(dropped.get());
+}
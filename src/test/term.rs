@@ -0,0 +1,106 @@
+use crate::{
+    component::{Component, id::id},
+    flecs::ChildOf,
+    query::{InOutKind, term::TermBuilder},
+    world::World,
+};
+
+struct Total {
+    sum: usize,
+}
+
+struct Value {
+    value: usize,
+}
+
+struct Marked;
+
+impl Component for Total {}
+impl Component for Value {}
+impl Component for Marked {}
+
+#[test]
+fn term_test() {
+    let mut world = World::new();
+    //register components
+    world.component::<Total>(c"Total");
+    world.component::<Value>(c"Value");
+    world.component::<Marked>(c"Marked");
+    world.singleton_set(Total { sum: 0 });
+    //create some entities
+    let parent = world.entity_named(c"parent");
+    parent.set_comp(Value { value: 100 });
+    let e1 = world.entity_named(c"e1");
+    let e2 = world.entity_named(c"e2");
+    let e3 = world.entity_named(c"e3");
+    e1.set_comp(Value { value: 1 });
+    e2.set_comp(Value { value: 2 });
+    e3.set_comp(Value { value: 4 });
+    e2.add(id::<Marked>());
+    e3.add((ChildOf, parent));
+
+    //query with a singleton and a negated term
+    let query = world
+        .query()
+        .with(id::<Value>())
+        .inout(InOutKind::In)
+        .with(id::<Total>())
+        .src(id::<Total>())
+        .inout(InOutKind::Out)
+        .without(id::<Marked>())
+        .build();
+    let mut query_iter = query.iter();
+    while query_iter.advance() {
+        let values = unsafe { query_iter.get::<Value>(0) }.unwrap();
+        let mut total = unsafe { query_iter.get::<Total>(1) }.unwrap();
+        for ent in 0..query_iter.count() {
+            total[ent].sum += values[ent].value;
+        }
+    }
+    assert_eq!(
+        unsafe { world.singleton_get::<Total>().unwrap().sum },
+        100 + 1 + 4
+    );
+    drop(query);
+
+    //query traversing the hierarchy
+    let query = world
+        .query()
+        .with(id::<Value>())
+        .with(id::<Value>())
+        .up(ChildOf)
+        .build();
+    let mut query_iter = query.iter();
+    let mut matched = 0;
+    while query_iter.advance() {
+        let values = unsafe { query_iter.get::<Value>(0) }.unwrap();
+        let parents = unsafe { query_iter.get::<Value>(1) }.unwrap();
+        assert_eq!(query_iter.source(1), Some(parent.id()));
+        for ent in 0..query_iter.count() {
+            assert_eq!(values[ent].value + parents[ent].value, 104);
+            matched += 1;
+        }
+    }
+    assert_eq!(matched, 1);
+    drop(query);
+
+    //system mixing terms with an expression
+    world.singleton_set(Total { sum: 0 });
+    world
+        .system_expr(c"Total($)")
+        .with(id::<Value>())
+        .optional(id::<Marked>())
+        .build_named(c"term_system", |iter| {
+            let values = unsafe { iter.get::<Value>(0) }.unwrap();
+            let mut total = unsafe { iter.get::<Total>(2) }.unwrap();
+            let multiplier = if iter.has(1) { 10 } else { 1 };
+            for ent in 0..iter.count() {
+                total[ent].sum += values[ent].value * multiplier;
+            }
+        });
+    world.progress();
+    assert_eq!(
+        unsafe { world.singleton_get::<Total>().unwrap().sum },
+        100 + 1 + 20 + 4
+    );
+}
@@ -0,0 +1,107 @@
+use crate::{
+    component::{Component, id::id},
+    query::term::TermBuilder,
+    world::World,
+};
+
+#[derive(Debug, PartialEq)]
+struct Position {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Debug, PartialEq)]
+struct Velocity {
+    x: f32,
+    y: f32,
+}
+
+struct Mass {
+    mass: f32,
+}
+
+struct Frozen;
+
+impl Component for Position {}
+impl Component for Velocity {}
+impl Component for Mass {}
+impl Component for Frozen {}
+
+#[test]
+fn typed_query_test() {
+    let mut world = World::new();
+    //register components
+    world.component::<Position>(c"Position");
+    world.component::<Velocity>(c"Velocity");
+    world.component::<Mass>(c"Mass");
+    world.component::<Frozen>(c"Frozen");
+    //create some entities
+    let light = world.entity_named(c"light");
+    light.set_comp(Position { x: 0.0, y: 0.0 });
+    light.set_comp(Velocity { x: 1.0, y: 2.0 });
+    let heavy = world.entity_named(c"heavy");
+    heavy.set_comp(Position { x: 10.0, y: 10.0 });
+    heavy.set_comp(Velocity { x: 4.0, y: 4.0 });
+    heavy.set_comp(Mass { mass: 2.0 });
+    let frozen = world.entity_named(c"frozen");
+    frozen.set_comp(Position { x: 5.0, y: 5.0 });
+    frozen.set_comp(Velocity { x: 1.0, y: 1.0 });
+    frozen.add(id::<Frozen>());
+
+    //move everything that is not frozen
+    let query = world
+        .query_typed::<(&mut Position, &Velocity, Option<&Mass>)>()
+        .without(id::<Frozen>())
+        .build();
+    let mut visited = 0;
+    query.each(|(position, velocity, mass)| {
+        let mass = mass.map(|mass| mass.mass).unwrap_or(1.0);
+        position.x += velocity.x / mass;
+        position.y += velocity.y / mass;
+        visited += 1;
+    });
+    assert_eq!(visited, 2);
+    assert_eq!(
+        unsafe { light.get::<Position>() }.unwrap(),
+        &Position { x: 1.0, y: 2.0 }
+    );
+    assert_eq!(
+        unsafe { heavy.get::<Position>() }.unwrap(),
+        &Position { x: 12.0, y: 12.0 }
+    );
+    assert_eq!(
+        unsafe { frozen.get::<Position>() }.unwrap(),
+        &Position { x: 5.0, y: 5.0 }
+    );
+
+    //table wide access
+    let mut total = 0.0;
+    query.run(|iter, (positions, _, masses)| {
+        for row in 0..iter.count() {
+            if masses.is_none() {
+                total += positions[row].x;
+            }
+        }
+    });
+    assert_eq!(total, 1.0);
+}
+
+#[test]
+#[should_panic]
+fn typed_query_aliasing_test() {
+    let mut world = World::new();
+    world.component::<Position>(c"Position");
+    let _query = world.query_typed::<(&mut Position, &Position)>().build();
+}
+
+#[test]
+#[should_panic]
+fn typed_field_bounds_test() {
+    let mut world = World::new();
+    world.component::<Position>(c"Position");
+    world.entity().set_comp(Position { x: 0.0, y: 0.0 });
+    let query = world.query_typed::<(&Position,)>().build();
+    query.run(|iter, (positions,)| {
+        let _ = &positions[iter.count()];
+    });
+}
@@ -1,24 +1,50 @@
+mod context;
+
 use ahash::AHashMap;
 use flecs_ecs_sys::*;
 use std::{
     any::TypeId,
+    cell::RefCell,
     ffi::{CStr, c_void},
+    ops::ControlFlow,
+    panic::resume_unwind,
     ptr::{NonNull, null_mut},
 };
 
 use crate::{
+    c_types::ECS_IS_A,
     component::{
         Component, ComponentView,
+        hooks::{
+            HookContext, copy_ctor_callback, default_ctor_callback, dtor_callback, hook_ctx_free,
+            rebound_hooks,
+        },
         id::{Id, IdFetcher, id},
+        id_view::IdView,
+        layout::{ComponentLayout, LAYOUT_SYMBOL, LayoutMismatch},
+    },
+    entity::{Entity, EntityView, iter::EntityIter},
+    event::EventBuilder,
+    flecs::{Inherit, OnInstantiate, Prefab, rest::Rest},
+    observer::ObserverBuilder,
+    query::{
+        QueryBuilder,
+        typed::{QueryData, TypedQueryBuilder},
     },
-    entity::{Entity, EntityView},
-    flecs::rest::Rest,
-    query::QueryBuilder,
     system::SystemBuilder,
 };
 
+pub use context::PanicPayload;
+pub(crate) use context::{
+    WorldContext, abort_on_panic, assert_unborrowed, catch_orphan_panic, catch_panic, is_panicking,
+    resume_orphan_panic,
+};
+
 /// Component map, mapping local typeids to registered entity ids.
-pub type ComponentMap = AHashMap<TypeId, Entity>;
+///
+/// Shared by all worlds of one crate referring to the same flecs world, so it is only ever
+/// borrowed for a single lookup or insertion.
+pub type ComponentMap = RefCell<AHashMap<TypeId, Entity>>;
 
 /// ECS world.
 #[derive(Debug)]
@@ -44,15 +70,21 @@ pub struct World {
 impl Default for World {
     fn default() -> Self {
         //leak component map
-        let component_map = Box::new(AHashMap::new());
+        let component_map = Box::new(RefCell::new(AHashMap::new()));
         let component_map = Box::leak(component_map);
+        //create the world with its context
+        let ptr = unsafe { NonNull::new(ecs_init()).expect("could not init ecs world") };
+        unsafe { WorldContext::get_or_init(ptr.as_ptr()) };
         //compose world
-        Self {
-            ptr: unsafe { NonNull::new(ecs_init()).expect("could not init ecs world") },
+        let world = Self {
+            ptr,
             owned: true,
             component_map: component_map.into(),
             map_owned: true,
-        }
+        };
+        //registered up front, so that it does not end up in the scope of a plugin
+        world.layout_component();
+        world
     }
 }
 
@@ -72,7 +104,7 @@ impl World {
     pub unsafe fn from_ptr(ptr: *mut ecs_world_t) -> Self {
         assert!(!ptr.is_null(), "cannot create a world from a null pointer");
         //leak component map
-        let component_map = Box::new(AHashMap::new());
+        let component_map = Box::new(RefCell::new(AHashMap::new()));
         let component_map = Box::leak(component_map);
         World {
             ptr: unsafe { NonNull::new_unchecked(ptr) },
@@ -123,8 +155,7 @@ impl Drop for World {
         }
         //clear component map
         if self.map_owned {
-            let component_map = unsafe { self.component_map.as_mut() };
-            let component_map = unsafe { Box::from_raw(component_map) };
+            let component_map = unsafe { Box::from_raw(self.component_map.as_ptr()) };
             drop(component_map);
         }
         //we do not own the world
@@ -164,6 +195,31 @@ impl World {
         }
     }
 
+    /// Creates a new named prefab.
+    ///
+    /// Prefabs are ignored by queries by default, they are templates for [Self::instantiate].
+    pub fn prefab(&self, name: &CStr) -> EntityView<'_> {
+        let prefab = self.entity_named(name);
+        prefab.add(Prefab);
+        prefab
+    }
+
+    /// Creates a new instance of a prefab.
+    ///
+    /// The instance inherits components of the prefab, components are copied to the instance
+    /// unless their [crate::flecs::OnInstantiate] trait says otherwise, see [ComponentView::on_instantiate].
+    /// Components which are dropped, but cannot be cloned, are inherited by default, see
+    /// [Self::component]. Children of the prefab are instantiated as children of the instance.
+    pub fn instantiate(&self, prefab: impl IdFetcher) -> EntityView<'_> {
+        let prefab = prefab.retrieve_id(self);
+        let entity = unsafe { ecs_new_w_id(self.ptr(), ecs_make_pair(ECS_IS_A, prefab)) };
+        self.resume_panic();
+        EntityView {
+            world: self,
+            entity_id: entity,
+        }
+    }
+
     /// Creates an entity view from a id.
     /// This does not create a new entity!
     #[inline]
@@ -174,6 +230,33 @@ impl World {
         }
     }
 
+    /// Iterates all entities with an id, which may be a wildcard pair such as `(Likes, *)`.
+    ///
+    /// Uncached, unlike a [crate::query::Query] it is cheap to create. Changes to the world are
+    /// deferred until the iterator is dropped, which can be done early.
+    pub fn entities_with(&self, id: impl IdFetcher) -> EntityIter<'_> {
+        let id = id.retrieve_id(self);
+        let iter = unsafe { ecs_each_id(self.ptr(), id) };
+        EntityIter::new(self, iter, ecs_each_next)
+    }
+
+    /// Calls a function for all entities with an id, until it breaks, see [Self::entities_with].
+    ///
+    /// Changes made by the function are deferred until the iteration ends.
+    pub fn each_id(
+        &self,
+        id: impl IdFetcher,
+        mut func: impl FnMut(EntityView<'_>) -> ControlFlow<()>,
+    ) {
+        let _ = self.entities_with(id).try_for_each(|entity| func(entity));
+    }
+
+    /// Creates a view of an id, which can be decomposed if it is a pair.
+    #[inline]
+    pub fn id_view(&self, id: impl IdFetcher) -> IdView<'_> {
+        IdView::new(self, id.retrieve_id(self))
+    }
+
     /// Lookup an entity by its name.
     pub fn lookup(&self, name: &CStr) -> Option<EntityView<'_>> {
         let entity = unsafe { ecs_lookup(self.ptr(), name.as_ptr()) };
@@ -203,19 +286,40 @@ impl World {
 
 impl World {
     /// Creates a new named data component or tag.
+    ///
+    /// # Panics
+    ///
+    /// If the symbol is already registered with a different layout, see [Self::try_component].
     pub fn component<T: Component>(&mut self, symbol: &CStr) -> ComponentView<'_> {
+        match self.try_component::<T>(symbol) {
+            Ok(component) => component,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Creates a new named data component or tag.
+    ///
+    /// If the symbol is already registered, for example by another binary sharing the world, its
+    /// layout must match the layout of `T`.
+    ///
+    /// Components which need to be dropped are inherited by instances of prefabs, instead of
+    /// being copied, until they get a copy hook, e.g. by
+    /// [crate::component::hooks::ComponentHooks::clone_copy].
+    pub fn try_component<T: Component>(
+        &mut self,
+        symbol: &CStr,
+    ) -> Result<ComponentView<'_>, LayoutMismatch> {
         //is it a tag
         if T::IS_TAG {
-            return self.tag::<T>(symbol);
+            return self.try_tag::<T>(symbol);
         }
         //is it already registered in flecs?
-        if let Some(entity) = self.lookup_symbol(symbol) {
-            let id = entity.entity_id;
-            unsafe { self.component_map.as_mut() }.insert(TypeId::of::<T>(), id);
-            return ComponentView {
+        if let Some(id) = self.existing_component::<T>(symbol, Self::data_hooks::<T>)? {
+            self.remember::<T>(id);
+            return Ok(ComponentView {
                 world: self,
                 entity_id: id,
-            };
+            });
         }
         //register component in flecs
         let edesc = ecs_entity_desc_t {
@@ -230,14 +334,7 @@ impl World {
             type_: ecs_type_info_t {
                 size: std::mem::size_of::<T>() as i32,
                 alignment: std::mem::align_of::<T>() as i32,
-                hooks: ecs_type_hooks_t {
-                    dtor: if T::NEEDS_DROP {
-                        Some(dtor_callback::<T>)
-                    } else {
-                        None
-                    },
-                    ..Default::default()
-                },
+                hooks: self.data_hooks::<T>(),
                 component: 0,
                 name: symbol.as_ptr(),
             },
@@ -245,16 +342,66 @@ impl World {
         let id = unsafe { ecs_component_init(self.ptr(), &cdesc as *const _) };
         //check it
         assert!(id != 0, "failed to register a component");
+        self.store_layout::<T>(id);
+        //copying its bytes to instances of prefabs would drop it twice
+        if T::NEEDS_DROP {
+            self.inherit_by_default(id);
+        }
+        self.record_reloaded(id);
         //remember final id
-        unsafe { self.component_map.as_mut() }.insert(TypeId::of::<T>(), id);
-        ComponentView {
+        self.remember::<T>(id);
+        Ok(ComponentView {
             world: self,
             entity_id: id,
+        })
+    }
+
+    /// Registers a component under the symbol of its type, see [Component::SYMBOL].
+    ///
+    /// Calls [Component::on_register] afterwards, unless the component was already registered,
+    /// since flecs rejects changes of traits and hooks of components in use. Components of a
+    /// reloaded plugin are set up again, their hooks were replaced.
+    pub fn register<T: Component>(&mut self) -> ComponentView<'_> {
+        let Some(symbol) = T::SYMBOL else {
+            panic!(
+                "component {:?} has no symbol, derive it or use World::component",
+                core::any::type_name::<T>()
+            );
+        };
+        let reloading =
+            unsafe { WorldContext::get(self.ptr()) }.is_some_and(|ctx| ctx.is_reloading());
+        let registered = self.lookup_symbol(symbol).is_some() && !reloading;
+        let component = if T::IS_TAG {
+            self.tag::<T>(symbol)
+        } else {
+            self.component::<T>(symbol)
+        };
+        if !registered {
+            T::on_register(component);
         }
+        component
     }
 
     /// Registers a new tag.
+    ///
+    /// # Panics
+    ///
+    /// If the symbol is already registered as a data component, see [Self::try_tag].
     pub fn tag<T: Component>(&mut self, symbol: &CStr) -> ComponentView<'_> {
+        match self.try_tag::<T>(symbol) {
+            Ok(component) => component,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Registers a new tag.
+    ///
+    /// If the symbol is already registered, for example by another binary sharing the world, it
+    /// must not hold data.
+    pub fn try_tag<T: Component>(
+        &mut self,
+        symbol: &CStr,
+    ) -> Result<ComponentView<'_>, LayoutMismatch> {
         //check if it is a tag
         if !T::IS_TAG {
             panic!("tag function only registers tags");
@@ -262,11 +409,13 @@ impl World {
         //is it already registered in flecs?
         if let Some(entity) = self.lookup_symbol(symbol) {
             let id = entity.entity_id;
-            unsafe { self.component_map.as_mut() }.insert(TypeId::of::<T>(), id);
-            return ComponentView {
+            self.check_layout::<T>(id, symbol)?;
+            self.record_reloaded(id);
+            self.remember::<T>(id);
+            return Ok(ComponentView {
                 world: self,
                 entity_id: id,
-            };
+            });
         }
         //register tag in flecs
         let edesc = ecs_entity_desc_t {
@@ -278,24 +427,36 @@ impl World {
         let id = unsafe { ecs_entity_init(self.ptr(), &edesc as *const _) };
         //check it
         assert!(id != 0, "failed to register a tag");
+        self.record_reloaded(id);
         //remember final id
-        unsafe { self.component_map.as_mut() }.insert(TypeId::of::<T>(), id);
-        ComponentView {
+        self.remember::<T>(id);
+        Ok(ComponentView {
             world: self,
             entity_id: id,
-        }
+        })
     }
 
     /// Creates a component with a copy constructor derived from Clone.
+    ///
+    /// flecs copies into new components before they are constructed, so copies do not drop the
+    /// component they overwrite, which is leaked. Components which also implement Default can be
+    /// copied with [crate::component::hooks::ComponentHooks::clone_copy] instead.
+    ///
+    /// # Panics
+    ///
+    /// If the symbol is already registered with a different layout.
     pub fn component_clone<T: Component + Clone>(&mut self, symbol: &CStr) -> ComponentView<'_> {
         //is it already registered in flecs?
-        if let Some(entity) = self.lookup(symbol) {
-            let id = entity.entity_id;
-            unsafe { self.component_map.as_mut() }.insert(TypeId::of::<T>(), id);
-            return ComponentView {
-                world: self,
-                entity_id: id,
-            };
+        match self.existing_component::<T>(symbol, Self::clone_hooks::<T>) {
+            Ok(Some(id)) => {
+                self.remember::<T>(id);
+                return ComponentView {
+                    world: self,
+                    entity_id: id,
+                };
+            }
+            Ok(None) => {}
+            Err(err) => panic!("{err}"),
         }
         //register component in flecs
         let edesc = ecs_entity_desc_t {
@@ -310,15 +471,7 @@ impl World {
             type_: ecs_type_info_t {
                 size: std::mem::size_of::<T>() as i32,
                 alignment: std::mem::align_of::<T>() as i32,
-                hooks: ecs_type_hooks_t {
-                    dtor: if T::NEEDS_DROP {
-                        Some(dtor_callback::<T>)
-                    } else {
-                        None
-                    },
-                    copy: Some(copy_callback::<T>),
-                    ..Default::default()
-                },
+                hooks: self.clone_hooks::<T>(),
                 component: 0,
                 name: symbol.as_ptr(),
             },
@@ -326,37 +479,220 @@ impl World {
         let id = unsafe { ecs_component_init(self.ptr(), &cdesc as *const _) };
         //check it
         assert!(id != 0, "failed to register a component");
+        self.store_layout::<T>(id);
+        self.record_reloaded(id);
         //remember final id
-        unsafe { self.component_map.as_mut() }.insert(TypeId::of::<T>(), id);
+        self.remember::<T>(id);
         ComponentView {
             world: self,
             entity_id: id,
         }
     }
-}
 
-unsafe extern "C" fn copy_callback<T: Component + Clone>(
-    dst: *mut c_void,
-    src: *const c_void,
-    count: i32,
-    _t_i: *const ecs_type_info_t,
-) {
-    for i in 0..count {
-        let src = unsafe { src.offset(i as isize) } as *mut T;
-        let dst = unsafe { dst.offset(i as isize) } as *mut T;
-        unsafe { std::ptr::write(dst, (*src).clone()) };
+    /// Resolves a component by its [Component::SYMBOL], when it is missing in the component map.
+    ///
+    /// Allows binaries sharing a world to use components registered by one another.
+    pub(crate) fn resolve_symbol<T: Component>(&self) -> Option<Entity> {
+        let symbol = T::SYMBOL?;
+        let id = self.lookup_symbol(symbol)?.entity_id;
+        if let Err(err) = self.check_layout::<T>(id, symbol) {
+            panic!("{err}");
+        }
+        //remember it
+        self.remember::<T>(id);
+        Some(id)
+    }
+
+    /// Makes instances of prefabs inherit a component, until it gets a copy hook.
+    fn inherit_by_default(&self, id: Entity) {
+        let trait_id = IdView::pair(self, OnInstantiate, Inherit).id();
+        unsafe { ecs_add_id(self.ptr(), id, trait_id) };
+        if let Some(ctx) = unsafe { HookContext::of(self, id).as_mut() } {
+            ctx.inherit_default = true;
+        }
+    }
+
+    /// Remembers the id of a component in the component map.
+    fn remember<T: Component>(&self, id: Entity) {
+        unsafe { self.component_map.as_ref() }
+            .borrow_mut()
+            .insert(TypeId::of::<T>(), id);
+    }
+
+    /// Hooks of a newly registered data component.
+    fn data_hooks<T: Component>(&self) -> ecs_type_hooks_t {
+        let hooks = ecs_type_hooks_t {
+            ctor: if T::DEFAULT.is_some() {
+                Some(default_ctor_callback::<T>)
+            } else {
+                None
+            },
+            dtor: if T::NEEDS_DROP {
+                Some(dtor_callback::<T>)
+            } else {
+                None
+            },
+            binding_ctx: HookContext::leak(self.ptr(), self.component_map.as_ptr()),
+            binding_ctx_free: Some(hook_ctx_free),
+            ..Default::default()
+        };
+        //it may be added without data
+        unsafe { (*(hooks.binding_ctx as *mut HookContext)).default_ctor = T::DEFAULT.is_some() };
+        hooks
+    }
+
+    /// Hooks of a newly registered data component with a copy constructor derived from Clone.
+    fn clone_hooks<T: Component + Clone>(&self) -> ecs_type_hooks_t {
+        ecs_type_hooks_t {
+            //the destination may only be zeroed, so it is not dropped
+            copy: Some(copy_ctor_callback::<T>),
+            copy_ctor: Some(copy_ctor_callback::<T>),
+            ..self.data_hooks::<T>()
+        }
+    }
+
+    /// Checks a data component already registered under a symbol.
+    ///
+    /// While a plugin is being reloaded, hooks of a component with an unchanged layout are
+    /// replaced by `hooks`, since the old ones point into the old library. A component whose
+    /// layout changed is deleted together with its data and `None` is returned, so that it is
+    /// registered again.
+    fn existing_component<T: Component>(
+        &self,
+        symbol: &CStr,
+        hooks: fn(&Self) -> ecs_type_hooks_t,
+    ) -> Result<Option<Entity>, LayoutMismatch> {
+        let Some(entity) = self.lookup_symbol(symbol) else {
+            return Ok(None);
+        };
+        let id = entity.entity_id;
+        let reloading =
+            unsafe { WorldContext::get(self.ptr()) }.is_some_and(|ctx| ctx.is_reloading());
+        match self.check_layout::<T>(id, symbol) {
+            Ok(()) if reloading => {
+                self.rebind_hooks::<T>(id, hooks(self));
+                self.record_reloaded(id);
+                Ok(Some(id))
+            }
+            Ok(()) => Ok(Some(id)),
+            Err(_) if reloading => {
+                //the old library still runs the destructors
+                unsafe { ecs_delete(self.ptr(), id) };
+                self.resume_panic();
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Replaces all hooks of a component registered by an older version of a reloaded plugin.
+    ///
+    /// The binding context of the old hooks is freed afterwards, flecs only replaces it.
+    fn rebind_hooks<T: Component>(&self, id: Entity, hooks: ecs_type_hooks_t) {
+        let hooks = rebound_hooks::<T>(hooks);
+        let (old_ctx, old_free) = match unsafe { ecs_get_type_info(self.ptr(), id).as_ref() } {
+            Some(type_info) => (
+                type_info.hooks.binding_ctx,
+                type_info.hooks.binding_ctx_free,
+            ),
+            None => (null_mut(), None),
+        };
+        unsafe { ecs_set_hooks_id(self.ptr(), id, &hooks as *const _) };
+        if let Some(free) = old_free.filter(|_| !old_ctx.is_null() && old_ctx != hooks.binding_ctx)
+        {
+            unsafe { free(old_ctx) };
+        }
+    }
+
+    /// Records a component registered while a plugin is being reloaded.
+    fn record_reloaded(&self, id: Entity) {
+        if let Some(ctx) = unsafe { WorldContext::get(self.ptr()) } {
+            ctx.record_reloaded(id);
+        }
+    }
+
+    /// Retrieves the id of [ComponentLayout], registering it if needed.
+    fn layout_component(&self) -> Entity {
+        let component_map = unsafe { self.component_map.as_ref() };
+        if let Some(id) = component_map.borrow().get(&TypeId::of::<ComponentLayout>()) {
+            return *id;
+        }
+        let id = match self.lookup_symbol(LAYOUT_SYMBOL) {
+            Some(entity) => entity.entity_id,
+            None => {
+                let edesc = ecs_entity_desc_t {
+                    name: LAYOUT_SYMBOL.as_ptr(),
+                    symbol: LAYOUT_SYMBOL.as_ptr(),
+                    use_low_id: true,
+                    ..Default::default()
+                };
+                let cdesc = ecs_component_desc_t {
+                    _canary: 0,
+                    entity: unsafe { ecs_entity_init(self.ptr(), &edesc as *const _) },
+                    type_: ecs_type_info_t {
+                        size: std::mem::size_of::<ComponentLayout>() as i32,
+                        alignment: std::mem::align_of::<ComponentLayout>() as i32,
+                        hooks: Default::default(),
+                        component: 0,
+                        name: LAYOUT_SYMBOL.as_ptr(),
+                    },
+                };
+                let id = unsafe { ecs_component_init(self.ptr(), &cdesc as *const _) };
+                assert!(id != 0, "failed to register a component");
+                id
+            }
+        };
+        component_map
+            .borrow_mut()
+            .insert(TypeId::of::<ComponentLayout>(), id);
+        id
     }
-}
 
-unsafe extern "C" fn dtor_callback<T: Component>(
-    ptr: *mut c_void,
-    count: i32,
-    _type_info: *const ecs_type_info_t,
-) {
-    for i in 0..count {
-        let ptr = unsafe { ptr.offset(i as isize) };
-        let thing = ptr as *mut T;
-        unsafe { thing.drop_in_place() };
+    /// Stores the layout fingerprint of a newly registered component.
+    fn store_layout<T: Component>(&self, id: Entity) {
+        let layout = ComponentLayout::of::<T>();
+        let layout_id = self.layout_component();
+        unsafe {
+            ecs_set_id(
+                self.ptr(),
+                id,
+                layout_id,
+                std::mem::size_of::<ComponentLayout>(),
+                &layout as *const _ as *const c_void,
+            )
+        };
+    }
+
+    /// Checks that an already registered component has the layout of `T`.
+    fn check_layout<T: Component>(&self, id: Entity, symbol: &CStr) -> Result<(), LayoutMismatch> {
+        let requested = ComponentLayout::of::<T>();
+        let layout_id = self.layout_component();
+        let stored = unsafe { ecs_get_id(self.ptr(), id, layout_id) } as *const ComponentLayout;
+        let registered = match unsafe { stored.as_ref() } {
+            Some(layout) => *layout,
+            None => {
+                //registered without a fingerprint, compare what flecs knows
+                let type_info = unsafe { ecs_get_type_info(self.ptr(), id) };
+                let Some(type_info) = (unsafe { type_info.as_ref() }) else {
+                    //not a data component yet
+                    return Ok(());
+                };
+                ComponentLayout {
+                    size: type_info.size as u32,
+                    alignment: type_info.alignment as u32,
+                    hash: 0,
+                }
+            }
+        };
+        if registered.is_compatible(&requested) {
+            Ok(())
+        } else {
+            Err(LayoutMismatch {
+                symbol: symbol.to_owned(),
+                registered,
+                requested,
+            })
+        }
     }
 }
 
@@ -395,25 +731,24 @@ impl World {
         unsafe { self.singleton::<T>().get_mut::<T>() }
     }
 
-    // Adds a tag singleton to the world.
-    //
-    // Tags are components without data.
-    //
-    // # Note
-    // Cannot add data components since they would be unitialized which is not allowed in Rust.
-    // singleton's don't make much sense as tags
-    //pub fn singleton_add<I: IdFetcher>(&self, id: I)
-    //where
-    //    I::COMPONENT: Component,
-    //{
-    //    const {
-    //        if !<I::COMPONENT as Component>::IS_TAG {
-    //            panic!("cannot add a data component as a singleton")
-    //        }
-    //    }
-    //    let id = id.retrieve_id(self);
-    //    self.singleton::<I::COMPONENT>().add(id)
-    //}
+    /// Adds a singleton to the world.
+    ///
+    /// Does not compile if the component has data but no [Component::DEFAULT] constructor, see
+    /// [EntityView::add].
+    pub fn singleton_add<T: Component>(&self, id: Id<T>) {
+        self.singleton::<T>().add(id);
+    }
+
+    /// Gets a mutable access to the singleton, adding it first if it is missing.
+    ///
+    /// Same as `self.singleton::<T>().ensure::<T>()`
+    ///
+    /// # Safety
+    ///
+    /// Same as `ensure` from EntityView.
+    pub unsafe fn singleton_ensure<T: Component>(&self) -> &mut T {
+        unsafe { self.singleton::<T>().ensure::<T>() }
+    }
 
     /// Sets data component a singleton.
     pub fn singleton_set<T: Component>(&self, data: T) {
@@ -446,6 +781,7 @@ impl World {
         QueryBuilder {
             inner: desc,
             expr: None,
+            term_count: 0,
             world: self,
         }
     }
@@ -458,11 +794,24 @@ impl World {
         let builder = QueryBuilder {
             inner: desc,
             expr: None,
+            term_count: 0,
             world: self,
         };
         builder.expression(expr)
     }
 
+    /// Creates a typed query builder from a signature.
+    ///
+    /// For example `world.query_typed::<(&Position, &mut Velocity, Option<&Mass>)>()`.
+    pub fn query_typed<'a, Q: QueryData>(&'a self) -> TypedQueryBuilder<'a, Q> {
+        //create the terms of the signature
+        let builder = Q::populate(self.query());
+        TypedQueryBuilder {
+            builder,
+            __m: std::marker::PhantomData,
+        }
+    }
+
     /// Creates an empty system builder.
     pub fn system<'a>(&'a self) -> SystemBuilder<'a> {
         //create an empty descriptor
@@ -472,6 +821,7 @@ impl World {
             kind: 0,
             inner: desc,
             expr: None,
+            term_count: 0,
             world: self,
         }
     }
@@ -485,10 +835,43 @@ impl World {
             inner: desc,
             kind: 0,
             expr: None,
+            term_count: 0,
             world: self,
         };
         builder.expression(expr)
     }
+
+    /// Creates an empty observer builder.
+    pub fn observer<'a>(&'a self) -> ObserverBuilder<'a> {
+        //create an empty descriptor
+        let desc = ecs_observer_desc_t::default();
+        //create a builder
+        ObserverBuilder {
+            inner: desc,
+            expr: None,
+            event_count: 0,
+            term_count: 0,
+            world: self,
+        }
+    }
+
+    /// Creates an observer builder from an expression.
+    pub fn observer_expr<'a>(&'a self, expr: &CStr) -> ObserverBuilder<'a> {
+        self.observer().expression(expr)
+    }
+
+    /// Creates a builder for emitting the custom event `E`.
+    ///
+    /// The event type must be registered as a component.
+    pub fn event<'a, E: Component>(&'a self) -> EventBuilder<'a, E> {
+        EventBuilder {
+            world: self,
+            event: id::<E>().retrieve_id(self),
+            ids: Vec::new(),
+            entity: 0,
+            __m: std::marker::PhantomData,
+        }
+    }
 }
 
 //------------------------------------------------------------------------------
@@ -540,18 +923,60 @@ impl World {
 
     /// Progresses the world.
     ///
-    /// Calls every system.
+    /// Calls every system. Panics of callbacks are resumed once the frame is over.
     #[inline]
     pub fn progress(&self) -> bool {
-        unsafe { ecs_progress(self.ptr(), 0.0) }
+        self.progress_deltatime(0.0)
     }
 
     /// Progresses the world with a specified delta time.
     ///
-    /// Calls every system.
+    /// Calls every system. Panics of callbacks are resumed once the frame is over.
     #[inline]
     pub fn progress_deltatime(&self, dt: f32) -> bool {
-        unsafe { ecs_progress(self.ptr(), dt) }
+        match self.try_progress_deltatime(dt) {
+            Ok(running) => running,
+            Err(payload) => resume_unwind(payload),
+        }
+    }
+
+    /// Progresses the world, returning a panic of a callback as an error.
+    ///
+    /// Once a callback panics, the rest of the callbacks in the frame are skipped.
+    #[inline]
+    pub fn try_progress(&self) -> Result<bool, PanicPayload> {
+        self.try_progress_deltatime(0.0)
+    }
+
+    /// Progresses the world with a specified delta time, returning a panic of a callback as an
+    /// error.
+    ///
+    /// Once a callback panics, the rest of the callbacks in the frame are skipped.
+    ///
+    /// # Panics
+    ///
+    /// If a component is borrowed by a guard, see [EntityView::get_ref].
+    pub fn try_progress_deltatime(&self, dt: f32) -> Result<bool, PanicPayload> {
+        unsafe { assert_unborrowed(self.ptr(), "progress the world") };
+        let running = unsafe { ecs_progress(self.ptr(), dt) };
+        match self.take_panic() {
+            Some(payload) => Err(payload),
+            None => Ok(running),
+        }
+    }
+
+    /// Takes out a panic caught inside a callback.
+    #[inline]
+    pub fn take_panic(&self) -> Option<PanicPayload> {
+        unsafe { context::take_panic(self.ptr()) }
+    }
+
+    /// Resumes a panic caught inside a callback, if there is any.
+    #[inline]
+    pub(crate) fn resume_panic(&self) {
+        if let Some(payload) = self.take_panic() {
+            resume_unwind(payload);
+        }
     }
 
     /// Imports and enabled REST api, allows you to connect using flecs explorer.
@@ -0,0 +1,276 @@
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    ffi::c_void,
+    panic::{AssertUnwindSafe, catch_unwind, resume_unwind},
+    process::abort,
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use ahash::AHashMap;
+use flecs_ecs_sys::*;
+
+use crate::entity::Entity;
+
+/// Payload of a panic caught inside a callback.
+pub type PanicPayload = Box<dyn Any + Send + 'static>;
+
+/// Rust side state of a world, stored as its binding context.
+pub(crate) struct WorldContext {
+    /// Is a panic waiting to be resumed?
+    pending: AtomicBool,
+    /// Panic caught inside a callback.
+    panic: Mutex<Option<PanicPayload>>,
+    /// Components registered during a plugin reload, None when not reloading.
+    reloaded: Mutex<Option<Vec<Entity>>>,
+    /// Borrowed components per entity and id, the number of shared borrows or -1 if borrowed
+    /// mutably.
+    borrows: Mutex<AHashMap<(Entity, Entity), isize>>,
+}
+
+thread_local! {
+    /// Panic caught in a callback which has no access to its world, such as order_by.
+    ///
+    /// Such callbacks run on the thread iterating, which resumes the panic.
+    static ORPHAN_PANIC: RefCell<Option<PanicPayload>> = const { RefCell::new(None) };
+    /// Is an orphan panic waiting to be resumed?
+    static ORPHAN_PENDING: Cell<bool> = const { Cell::new(false) };
+}
+
+impl WorldContext {
+    /// Version of the contexts shared with plugins through the world, bumped on every change of
+    /// their fields, see [crate::plugin::context_layout_hash].
+    pub(crate) const VERSION: u32 = 3;
+
+    /// Retrieves the context of a world, creating it if there is none.
+    ///
+    /// # Safety
+    ///
+    /// The pointer must be a valid pointer to a world or a stage, whose binding context is either
+    /// unset or set by this crate. The context must not outlive the world.
+    pub(crate) unsafe fn get_or_init<'a>(world: *const ecs_world_t) -> &'a WorldContext {
+        //stages share the context of their world
+        let world = unsafe { ecs_get_world(world as *const c_void) } as *mut ecs_world_t;
+        let ctx = unsafe { ecs_get_binding_ctx(world) } as *const WorldContext;
+        if let Some(ctx) = unsafe { ctx.as_ref() } {
+            return ctx;
+        }
+        //leak a new one, it is freed together with the world
+        let ctx = Box::leak(Box::new(WorldContext {
+            pending: AtomicBool::new(false),
+            panic: Mutex::new(None),
+            reloaded: Mutex::new(None),
+            borrows: Mutex::new(AHashMap::new()),
+        }));
+        unsafe {
+            ecs_set_binding_ctx(
+                world,
+                ctx as *mut _ as *mut c_void,
+                Some(world_context_free),
+            )
+        };
+        ctx
+    }
+
+    /// Retrieves the context of a world, if there is any.
+    ///
+    /// # Safety
+    ///
+    /// Same as [Self::get_or_init].
+    pub(crate) unsafe fn get<'a>(world: *const ecs_world_t) -> Option<&'a WorldContext> {
+        let world = unsafe { ecs_get_world(world as *const c_void) };
+        let ctx = unsafe { ecs_get_binding_ctx(world) } as *const WorldContext;
+        unsafe { ctx.as_ref() }
+    }
+
+    /// Stores a caught panic, keeping the first one if there is already one.
+    fn store_panic(&self, payload: PanicPayload) {
+        let mut panic = self.panic.lock().unwrap_or_else(|err| err.into_inner());
+        if panic.is_none() {
+            *panic = Some(payload);
+        }
+        self.pending.store(true, Ordering::Release);
+    }
+
+    /// Starts recording components registered during a plugin reload.
+    pub(crate) fn begin_reload(&self) {
+        *self.reloaded.lock().unwrap_or_else(|err| err.into_inner()) = Some(Vec::new());
+    }
+
+    /// Stops recording, returns the components registered during the reload.
+    pub(crate) fn end_reload(&self) -> Vec<Entity> {
+        let mut reloaded = self.reloaded.lock().unwrap_or_else(|err| err.into_inner());
+        reloaded.take().unwrap_or_default()
+    }
+
+    /// Is a plugin being reloaded?
+    pub(crate) fn is_reloading(&self) -> bool {
+        let reloaded = self.reloaded.lock().unwrap_or_else(|err| err.into_inner());
+        reloaded.is_some()
+    }
+
+    /// Records a component registered during a plugin reload.
+    pub(crate) fn record_reloaded(&self, id: Entity) {
+        let mut reloaded = self.reloaded.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(reloaded) = reloaded.as_mut() {
+            reloaded.push(id);
+        }
+    }
+
+    /// Records a borrow of a component of an entity.
+    ///
+    /// Fails if it conflicts with a borrow which is still alive.
+    pub(crate) fn borrow(&self, entity: Entity, id: Entity, mutable: bool) -> bool {
+        let mut borrows = self.borrows.lock().unwrap_or_else(|err| err.into_inner());
+        let state = borrows.entry((entity, id)).or_insert(0);
+        match (*state, mutable) {
+            (0, true) => *state = -1,
+            (0.., false) => *state += 1,
+            _ => return false,
+        }
+        true
+    }
+
+    /// Releases a borrow recorded by [Self::borrow].
+    pub(crate) fn release(&self, entity: Entity, id: Entity) {
+        let mut borrows = self.borrows.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(state) = borrows.get_mut(&(entity, id)) {
+            if *state > 1 {
+                *state -= 1;
+            } else {
+                borrows.remove(&(entity, id));
+            }
+        }
+    }
+
+    /// Is any component borrowed?
+    fn is_borrowed(&self) -> bool {
+        let borrows = self.borrows.lock().unwrap_or_else(|err| err.into_inner());
+        !borrows.is_empty()
+    }
+
+    /// Takes the caught panic out, if there is one.
+    fn take_panic(&self) -> Option<PanicPayload> {
+        if !self.pending.load(Ordering::Acquire) {
+            return None;
+        }
+        let mut panic = self.panic.lock().unwrap_or_else(|err| err.into_inner());
+        self.pending.store(false, Ordering::Release);
+        panic.take()
+    }
+}
+
+unsafe extern "C" fn world_context_free(ctx: *mut c_void) {
+    // SAFETY:
+    // ctx is a pointer to WorldContext, so we can safely cast it back.
+    let _ = unsafe { Box::from_raw(ctx as *mut WorldContext) };
+}
+
+/// Is a panic of a world waiting to be resumed?
+///
+/// Callbacks of systems and observers are skipped while it is.
+///
+/// # Safety
+///
+/// Same as [WorldContext::get_or_init].
+pub(crate) unsafe fn is_panicking(world: *const ecs_world_t) -> bool {
+    let pending = match unsafe { WorldContext::get(world) } {
+        Some(ctx) => ctx.pending.load(Ordering::Acquire),
+        None => false,
+    };
+    pending || ORPHAN_PENDING.get()
+}
+
+/// Panics while a component of a world is borrowed by a guard.
+///
+/// Iterating queries and running systems hands out references, which could alias the borrowed
+/// component.
+///
+/// # Safety
+///
+/// Same as [WorldContext::get_or_init].
+pub(crate) unsafe fn assert_unborrowed(world: *const ecs_world_t, action: &str) {
+    if unsafe { WorldContext::get(world) }.is_some_and(|ctx| ctx.is_borrowed()) {
+        panic!("cannot {action} while a component is borrowed by a guard");
+    }
+}
+
+/// Calls a function, catching its panic so it does not unwind into flecs.
+///
+/// The payload is stored in the world and `default` is returned instead.
+///
+/// # Safety
+///
+/// Same as [WorldContext::get_or_init].
+pub(crate) unsafe fn catch_panic<R>(
+    world: *const ecs_world_t,
+    default: R,
+    func: impl FnOnce() -> R,
+) -> R {
+    match catch_unwind(AssertUnwindSafe(func)) {
+        Ok(value) => value,
+        Err(payload) => {
+            unsafe { WorldContext::get_or_init(world) }.store_panic(payload);
+            default
+        }
+    }
+}
+
+/// Calls a function which must not leave its output uninitialized, such as a ctor hook.
+///
+/// Aborts the process on panic, since flecs would later use the output anyway.
+pub(crate) fn abort_on_panic<R>(func: impl FnOnce() -> R) -> R {
+    match catch_unwind(AssertUnwindSafe(func)) {
+        Ok(value) => value,
+        //the panic hook already reported the panic
+        Err(_) => abort(),
+    }
+}
+
+/// Calls a function without access to its world, catching its panic.
+///
+/// The payload is stored for the current thread and resumed by the first world to check for
+/// panics on it.
+pub(crate) fn catch_orphan_panic<R>(default: R, func: impl FnOnce() -> R) -> R {
+    match catch_unwind(AssertUnwindSafe(func)) {
+        Ok(value) => value,
+        Err(payload) => {
+            ORPHAN_PANIC.with_borrow_mut(|panic| {
+                if panic.is_none() {
+                    *panic = Some(payload);
+                }
+            });
+            ORPHAN_PENDING.set(true);
+            default
+        }
+    }
+}
+
+/// Takes out a panic caught inside a callback of a world.
+///
+/// # Safety
+///
+/// Same as [WorldContext::get_or_init].
+pub(crate) unsafe fn take_panic(world: *const ecs_world_t) -> Option<PanicPayload> {
+    //panics of the world come first
+    if let Some(payload) = unsafe { WorldContext::get(world) }.and_then(|ctx| ctx.take_panic()) {
+        return Some(payload);
+    }
+    if !ORPHAN_PENDING.replace(false) {
+        return None;
+    }
+    ORPHAN_PANIC.take()
+}
+
+/// Resumes a panic caught in a callback without access to its world, if there is any.
+pub(crate) fn resume_orphan_panic() {
+    if !ORPHAN_PENDING.replace(false) {
+        return;
+    }
+    if let Some(payload) = ORPHAN_PANIC.take() {
+        resume_unwind(payload);
+    }
+}
@@ -0,0 +1,70 @@
+//! Plugin used by the plugin tests of simple_flecs.
+
+use std::ffi::CStr;
+
+use simple_flecs::{flecs::OnSet, prelude::*};
+
+/// Score counted by the plugin.
+struct Score {
+    value: u32,
+}
+
+impl Component for Score {
+    const SYMBOL: Option<&'static CStr> = Some(c"fixture.Score");
+}
+
+/// Bonus whose layout changes in the second version of the plugin.
+#[allow(dead_code)]
+struct Bonus {
+    value: u32,
+    #[cfg(feature = "v2")]
+    extra: u32,
+}
+
+impl Component for Bonus {
+    const SYMBOL: Option<&'static CStr> = Some(c"fixture.Bonus");
+}
+
+/// Score added every frame.
+#[cfg(not(feature = "v2"))]
+const STEP: u32 = 1;
+#[cfg(feature = "v2")]
+const STEP: u32 = 10;
+
+/// Set once the plugin observed a score.
+struct Observed;
+
+impl Component for Observed {
+    const SYMBOL: Option<&'static CStr> = Some(c"fixture.Observed");
+}
+
+fn init(world: &mut World) {
+    world.register::<Score>();
+    world.register::<Observed>();
+    world.register::<Bonus>();
+    //counts scores up every frame
+    world
+        .system()
+        .with(id::<Score>())
+        .build_named(c"score_system", |iter| {
+            let mut score = unsafe { iter.get::<Score>(0) }.unwrap();
+            for i in 0..iter.count() {
+                score[i].value += STEP;
+            }
+        });
+    //marks entities whose score was set
+    world
+        .observer()
+        .with(id::<Score>())
+        .event(OnSet)
+        .build_named(c"score_observer", |iter| {
+            let world = iter.world();
+            for i in 0..iter.count() {
+                world.view(iter.entity(i).unwrap()).add(id::<Observed>());
+            }
+        });
+    #[cfg(feature = "broken")]
+    panic!("broken plugin");
+}
+
+simple_flecs::export_plugin!(c"score_plugin", init);